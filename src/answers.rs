@@ -0,0 +1,188 @@
+//! Known good answers, recorded in `data/<day>/answers`.
+//!
+//! Each part is written as `<part>: <answer>`. Multi-line answers (the
+//! activation code of day 13) put nothing after the colon and follow with
+//! one `| `-prefixed line per row. Empty lines and `#` comments are ignored:
+//!
+//! ```text
+//! 1: 621
+//! 2:
+//! | #  #
+//! | ####
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::parse;
+use crate::solution::{Answer, ParseError};
+
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct Answers(BTreeMap<u8, String>);
+
+/// Outcome of checking an answer against the recorded one
+#[derive(Debug, PartialEq, Clone)]
+pub enum Verdict {
+    PASS,
+    FAIL(String),   // The recorded answer
+    MISSING,
+}
+
+/// Answers are compared as printed, ignoring trailing newlines of text answers
+fn normalize(answer: &Answer) -> String {
+    answer.to_string().trim_end_matches('\n').to_string()
+}
+
+impl Answers {
+    pub fn get(self: &Self, part: u8) -> Option<&str> {
+        self.0.get(&part).map(String::as_str)
+    }
+
+    pub fn insert(self: &mut Self, part: u8, answer: &Answer) {
+        self.0.insert(part, normalize(answer));
+    }
+
+    pub fn verify(self: &Self, part: u8, answer: &Answer) -> Verdict {
+        match self.get(part) {
+            None => Verdict::MISSING,
+            Some(recorded) if recorded == normalize(answer) => Verdict::PASS,
+            Some(recorded) => Verdict::FAIL(recorded.to_string()),
+        }
+    }
+}
+
+impl FromStr for Answers {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut answers = BTreeMap::new();
+        let mut block: Option<(u8, Vec<&str>)> = None;  // Multi-line answer being read
+        for (i, line) in s.lines().enumerate() {
+            if let Some(row) = line.strip_prefix('|') {
+                match block.as_mut() {
+                    Some((_, rows)) => rows.push(row.strip_prefix(' ').unwrap_or(row)),
+                    None => return Err(ParseError::new(line, "<part>: before the answer rows").line(i + 1)),
+                }
+                continue;
+            }
+            if let Some((part, rows)) = block.take() {
+                answers.insert(part, rows.join("\n"));
+            }
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') { continue; }
+            let (part, answer) = trimmed
+                .split_once(':')
+                .ok_or_else(|| ParseError::at(line, trimmed, "<part>: <answer>").line(i + 1))?;
+            let part = parse::token::<u8>(line, part.trim(), "a part number").map_err(|e| e.line(i + 1))?;
+            match answer.trim() {
+                "" => block = Some((part, Vec::new())),
+                answer => { answers.insert(part, answer.to_string()); }
+            }
+        }
+        if let Some((part, rows)) = block {
+            answers.insert(part, rows.join("\n"));
+        }
+        Ok(Answers(answers))
+    }
+}
+
+impl fmt::Display for Answers {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (part, answer) in &self.0 {
+            if answer.contains('\n') {
+                writeln!(f, "{}:", part)?;
+                for row in answer.split('\n') {
+                    writeln!(f, "| {}", row)?;
+                }
+            }
+            else {
+                writeln!(f, "{}: {}", part, answer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn answers_path(data_dir: &Path, day: u8) -> PathBuf {
+    data_dir.join(day.to_string()).join("answers")
+}
+
+/// The recorded answers of a day, empty if none were recorded yet
+pub fn load(data_dir: &Path, day: u8) -> io::Result<Answers> {
+    read(&answers_path(data_dir, day))
+}
+
+/// Answers in the format above, empty if the file does not exist
+pub fn read(path: &Path) -> io::Result<Answers> {
+    match fs::read_to_string(path) {
+        Ok(s) => s
+            .parse()
+            .map_err(|e: ParseError| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e))),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Answers::default()),
+        Err(e) => Err(io::Error::new(e.kind(), format!("Cannot read {}: {}", path.display(), e))),
+    }
+}
+
+pub fn save(data_dir: &Path, day: u8, answers: &Answers) -> io::Result<()> {
+    let path = answers_path(data_dir, day);
+    fs::write(&path, answers.to_string())
+        .map_err(|e| io::Error::new(e.kind(), format!("Cannot write {}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::inputs::DATA_DIR;
+
+    const ANSWERS: &str = "\
+# Day 13
+1: 621
+
+2:
+| #  #
+|  ##
+|
+";
+
+    #[test]
+    fn parse_answers() {
+        let answers: Answers = ANSWERS.parse().unwrap();
+        assert_eq!(Some("621"), answers.get(1));
+        assert_eq!(Some("#  #\n ##\n"), answers.get(2));
+        assert_eq!(None, answers.get(3));
+    }
+
+    #[test]
+    fn parse_answers_error() {
+        let err = "1: 3\nx: 4\n".parse::<Answers>().unwrap_err();
+        assert_eq!((Some(2), 0), (err.line, err.column));
+        assert!("| ##\n".parse::<Answers>().is_err());
+        assert!("1 3\n".parse::<Answers>().is_err());
+    }
+
+    #[test]
+    fn answers_round_trip() {
+        let mut answers = Answers::default();
+        answers.insert(1, &Answer::Integer(-3));
+        answers.insert(2, &Answer::Text("# \n #\n".into()));
+        assert_eq!("1: -3\n2:\n| # \n|  #\n", answers.to_string());
+        assert_eq!(Ok(answers.clone()), answers.to_string().parse());
+    }
+
+    #[test]
+    fn verify_answers() {
+        let answers: Answers = "1: 42\n".parse().unwrap();
+        assert_eq!(Verdict::PASS, answers.verify(1, &Answer::Integer(42)));
+        assert_eq!(Verdict::FAIL("42".into()), answers.verify(1, &Answer::Integer(41)));
+        assert_eq!(Verdict::MISSING, answers.verify(2, &Answer::Integer(42)));
+    }
+
+    #[test]
+    fn load_recorded_answers() {
+        assert_eq!(Some("1602"), load(Path::new(DATA_DIR), 1).unwrap().get(1));
+        assert_eq!(Answers::default(), load(Path::new("no/such/dir"), 1).unwrap());
+    }
+}
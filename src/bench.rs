@@ -0,0 +1,158 @@
+use std::fmt;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::params::Values;
+use crate::parse::Context;
+use crate::registry::Day;
+use crate::solution::ParseError;
+
+/// The separately timed phases of solving a day
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Phase {
+    PARSE,
+    PART(u8),
+}
+
+impl fmt::Display for Phase {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Phase::PARSE => write!(f, "parse"),
+            Phase::PART(part) => write!(f, "part{}", part),
+        }
+    }
+}
+
+/// Summary of the samples of one phase
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+}
+
+impl Stats {
+    /// Panics if there are no samples
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let n = sorted.len();
+        Stats {
+            min: sorted[0],
+            median: sorted[(n - 1) / 2],
+            p95: sorted[(n * 95).div_ceil(100) - 1], // Nearest rank
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Timing {
+    pub day: u8,
+    pub phase: Phase,
+    pub stats: Stats,
+}
+
+fn time<T, F: FnMut() -> T>(iterations: usize, mut f: F) -> (Vec<Duration>, T) {
+    let mut samples = Vec::with_capacity(iterations);
+    let mut result = None;
+    for _ in 0..iterations.max(1) {
+        let start = Instant::now();
+        let value = black_box(f());
+        samples.push(start.elapsed());
+        result = Some(value);
+    }
+    (samples, result.unwrap())
+}
+
+/// Time parsing the input and solving each of `parts` over `iterations` runs.
+/// All parts are solved from the result of the last parse.
+pub fn bench_day(day: &Day, parts: &[u8], input: &str, iterations: usize) -> Result<Vec<Timing>, ParseError> {
+    let (samples, parsed) = time(iterations, || (day.parse)(input, &Values::new(), &Context::default()));
+    let parsed = parsed?;
+    let mut timings = vec![Timing{ day: day.day, phase: Phase::PARSE, stats: Stats::from_samples(&samples) }];
+    for &part in parts {
+        let (samples, _) = time(iterations, || parsed.solve(part));
+        timings.push(Timing{ day: day.day, phase: Phase::PART(part), stats: Stats::from_samples(&samples) });
+    }
+    Ok(timings)
+}
+
+/// Human readable table of the timings
+pub fn table(timings: &[Timing]) -> String {
+    let mut table = format!("{:>3}  {:<6} {:>12} {:>12} {:>12}\n", "day", "phase", "min", "median", "p95");
+    for t in timings {
+        table += &format!(
+            "{:>3}  {:<6} {:>12} {:>12} {:>12}\n",
+            t.day,
+            t.phase.to_string(),
+            format!("{:.1?}", t.stats.min),
+            format!("{:.1?}", t.stats.median),
+            format!("{:.1?}", t.stats.p95),
+        );
+    }
+    table
+}
+
+/// Report with durations in nanoseconds, one row per day and phase
+pub fn to_csv(timings: &[Timing], iterations: usize) -> String {
+    let mut csv = String::from("day,phase,iterations,min_ns,median_ns,p95_ns\n");
+    for t in timings {
+        csv += &format!(
+            "{},{},{},{},{},{}\n",
+            t.day, t.phase, iterations, t.stats.min.as_nanos(), t.stats.median.as_nanos(), t.stats.p95.as_nanos()
+        );
+    }
+    csv
+}
+
+/// Report with durations in nanoseconds
+pub fn to_json(timings: &[Timing], iterations: usize) -> String {
+    let rows: Vec<String> = timings
+        .iter()
+        .map(|t| format!(
+            "    {{\"day\": {}, \"phase\": \"{}\", \"min_ns\": {}, \"median_ns\": {}, \"p95_ns\": {}}}",
+            t.day, t.phase, t.stats.min.as_nanos(), t.stats.median.as_nanos(), t.stats.p95.as_nanos()
+        ))
+        .collect();
+    format!("{{\n  \"iterations\": {},\n  \"timings\": [\n{}\n  ]\n}}\n", iterations, rows.join(",\n"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::registry::DAYS;
+
+    fn ms(v: u64) -> Duration {
+        Duration::from_millis(v)
+    }
+
+    #[test]
+    fn stats_from_samples() {
+        let samples: Vec<Duration> = (1..=20).rev().map(ms).collect();
+        assert_eq!(Stats{ min: ms(1), median: ms(10), p95: ms(19) }, Stats::from_samples(&samples));
+        assert_eq!(Stats{ min: ms(3), median: ms(3), p95: ms(3) }, Stats::from_samples(&[ms(3)]));
+    }
+
+    #[test]
+    fn bench_phases() {
+        let day = DAYS.iter().find(|d| d.day == 1).unwrap();
+        let timings = bench_day(day, &[2], "199\n200\n208\n210\n200\n207\n", 3).unwrap();
+        let phases: Vec<Phase> = timings.iter().map(|t| t.phase).collect();
+        assert_eq!(vec![Phase::PARSE, Phase::PART(2)], phases);
+        assert!(bench_day(day, &[1], "x", 3).is_err());
+    }
+
+    #[test]
+    fn reports() {
+        let stats = Stats{ min: ms(1), median: ms(2), p95: ms(3) };
+        let timings = [Timing{ day: 7, phase: Phase::PART(1), stats }];
+        assert_eq!(
+            "day,phase,iterations,min_ns,median_ns,p95_ns\n7,part1,5,1000000,2000000,3000000\n",
+            to_csv(&timings, 5)
+        );
+        assert_eq!(
+            "{\n  \"iterations\": 5,\n  \"timings\": [\n    {\"day\": 7, \"phase\": \"part1\", \"min_ns\": 1000000, \"median_ns\": 2000000, \"p95_ns\": 3000000}\n  ]\n}\n",
+            to_json(&timings, 5)
+        );
+    }
+}
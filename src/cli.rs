@@ -0,0 +1,475 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use advent_of_code::inputs::Source;
+use advent_of_code::output::{Format, ParseFormatError};
+use advent_of_code::params::{self, ParamError};
+use advent_of_code::parse::Mode;
+use advent_of_code::registry::{ParseSelectorError, Selector};
+
+pub const USAGE: &str = "\
+Usage: advent_of_code run [options] <selection>...
+       advent_of_code bench [bench options] [selection]...
+       advent_of_code verify [--record] [selection]...
+       advent_of_code examples [selection]...
+       advent_of_code fetch [fetch options] [selection]...
+       advent_of_code sonar [sonar options]
+       advent_of_code help [day]
+       advent_of_code run <day> --help
+
+Options:
+    --input <file>   read the puzzle input from <file> ('-' for stdin)
+                     instead of data/<day>/input
+    --strict         reject input with malformed lines (default)
+    --lenient        skip malformed lines and list items and report
+                     which were skipped
+    --format <fmt>   print the answers as text (default), json (one
+                     record per line) or tsv
+    --param <key>=<value>
+                     override a parameter of the selected days, see
+                     'help <day>' for the parameters of a day
+
+Bench options (all days if no selection is given):
+    --iterations <n> time each phase over <n> runs (default 10)
+    --report <file>  write the timings to <file>, as CSV if it ends
+                     in .csv and as JSON otherwise
+
+Verify and examples check all days if no selection is given.
+
+Verify options:
+    --record         store the answers in data/<day>/answers
+
+Fetch downloads the missing inputs of all days if no selection is given.
+Cached inputs are never downloaded again.
+
+Fetch options:
+    --session <token>  session cookie of a logged in user (default
+                       $AOC_SESSION)
+    --base-url <url>   download from <url>/day/<day>/input (default
+                       $AOC_BASE_URL or https://adventofcode.com/2021)
+
+Sonar summarises the day 1 depths: their range, the jumps between
+consecutive depths and a sparkline of the depth and its moving average.
+
+Sonar options:
+    --input <file>     read the depths from <file> ('-' for stdin)
+                       instead of data/1/input
+    --window <n>       depths per moving average (default 3)
+    --threshold <n>    list the jumps larger than <n> (default 30)
+    --width <n>        characters per sparkline (default 60)
+    --strict           reject depths with malformed lines (default)
+    --lenient          skip malformed lines and report which were skipped
+
+Selections:
+    all       every day and part
+    9         both parts of day 9
+    9.2       only part 2 of day 9
+    3..7      days 3 to 7 (inclusive)";
+
+#[derive(Debug, PartialEq)]
+pub struct Run {
+    pub selectors: Vec<Selector>,
+    pub input: Source,
+    pub mode: Mode,
+    pub format: Format,
+    pub params: Vec<(String, String)>,
+}
+
+pub const DEFAULT_ITERATIONS: usize = 10;
+
+#[derive(Debug, PartialEq)]
+pub struct Bench {
+    pub selectors: Vec<Selector>,
+    pub iterations: usize,
+    pub report: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Verify {
+    pub selectors: Vec<Selector>,
+    pub record: bool,
+}
+
+pub const DEFAULT_WINDOW: usize = 3;
+pub const DEFAULT_THRESHOLD: u32 = 30;
+pub const DEFAULT_WIDTH: usize = 60;
+
+#[derive(Debug, PartialEq)]
+pub struct Sonar {
+    pub input: Source,
+    pub mode: Mode,
+    pub window: usize,
+    pub threshold: u32,
+    pub width: usize,
+}
+
+/// Options left out fall back to the environment
+#[derive(Debug, PartialEq)]
+pub struct Fetch {
+    pub selectors: Vec<Selector>,
+    pub session: Option<String>,
+    pub base_url: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run(Run),
+    Bench(Bench),
+    Verify(Verify),
+    Examples(Vec<Selector>),
+    Fetch(Fetch),
+    Sonar(Sonar),
+    Help(Option<u8>),   // General usage, or the parameters of a day
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CliError {
+    MissingCommand,
+    UnknownCommand(String),
+    MissingSelection,
+    MissingValue(String),
+    InvalidValue(String, String),
+    UnknownOption(String),
+    Selection(ParseSelectorError),
+    Format(ParseFormatError),
+    Param(ParamError),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::MissingCommand => write!(f, "No command given"),
+            CliError::UnknownCommand(c) => write!(f, "Unknown command '{}'", c),
+            CliError::MissingSelection => write!(f, "No puzzles selected"),
+            CliError::MissingValue(o) => write!(f, "Option '{}' requires a value", o),
+            CliError::InvalidValue(o, v) => write!(f, "Invalid value '{}' for option '{}'", v, o),
+            CliError::UnknownOption(o) => write!(f, "Unknown option '{}'", o),
+            CliError::Selection(e) => e.fmt(f),
+            CliError::Format(e) => e.fmt(f),
+            CliError::Param(e) => e.fmt(f),
+        }
+    }
+}
+
+impl Command {
+    pub fn parse<S: AsRef<str>>(args: &[S]) -> Result<Self, CliError> {
+        let mut args = args.iter().map(AsRef::as_ref);
+        match args.next() {
+            Some("run") => {
+                let mut run = Run{
+                    selectors: Vec::new(),
+                    input: Source::Default,
+                    mode: Mode::Strict,
+                    format: Format::TEXT,
+                    params: Vec::new(),
+                };
+                let mut help = false;
+                while let Some(arg) = args.next() {
+                    match arg {
+                        "--input" => {
+                            let value = args.next().ok_or_else(|| CliError::MissingValue(arg.into()))?;
+                            run.input = Source::from(value);
+                        }
+                        "--format" => {
+                            let value = args.next().ok_or_else(|| CliError::MissingValue(arg.into()))?;
+                            run.format = value.parse().map_err(CliError::Format)?;
+                        }
+                        "--param" => {
+                            let value = args.next().ok_or_else(|| CliError::MissingValue(arg.into()))?;
+                            run.params.push(params::split(value).map_err(CliError::Param)?);
+                        }
+                        "--strict" => run.mode = Mode::Strict,
+                        "--lenient" => run.mode = Mode::Lenient,
+                        "-h" | "--help" => help = true,
+                        option if option.starts_with("--") => {
+                            return Err(CliError::UnknownOption(option.into()));
+                        }
+                        selection => {
+                            run.selectors.push(selection.parse().map_err(CliError::Selection)?);
+                        }
+                    }
+                }
+                if help {
+                    // The parameters of a single selected day, the general usage otherwise
+                    return Ok(Command::Help(match run.selectors[..] {
+                        [Selector::Day(day) | Selector::Part(day, _)] => Some(day),
+                        _ => None,
+                    }));
+                }
+                if run.selectors.is_empty() { return Err(CliError::MissingSelection); }
+                Ok(Command::Run(run))
+            }
+            Some("bench") => {
+                let mut bench = Bench{ selectors: Vec::new(), iterations: DEFAULT_ITERATIONS, report: None };
+                while let Some(arg) = args.next() {
+                    match arg {
+                        "--iterations" => {
+                            let value = args.next().ok_or_else(|| CliError::MissingValue(arg.into()))?;
+                            bench.iterations = value
+                                .parse()
+                                .ok()
+                                .filter(|&n| n > 0)
+                                .ok_or_else(|| CliError::InvalidValue(arg.into(), value.into()))?;
+                        }
+                        "--report" => {
+                            let value = args.next().ok_or_else(|| CliError::MissingValue(arg.into()))?;
+                            bench.report = Some(PathBuf::from(value));
+                        }
+                        option if option.starts_with("--") => {
+                            return Err(CliError::UnknownOption(option.into()));
+                        }
+                        selection => {
+                            bench.selectors.push(selection.parse().map_err(CliError::Selection)?);
+                        }
+                    }
+                }
+                if bench.selectors.is_empty() { bench.selectors.push(Selector::All); }
+                Ok(Command::Bench(bench))
+            }
+            Some("verify") => {
+                let mut verify = Verify{ selectors: Vec::new(), record: false };
+                for arg in args {
+                    match arg {
+                        "--record" => verify.record = true,
+                        option if option.starts_with("--") => {
+                            return Err(CliError::UnknownOption(option.into()));
+                        }
+                        selection => {
+                            verify.selectors.push(selection.parse().map_err(CliError::Selection)?);
+                        }
+                    }
+                }
+                if verify.selectors.is_empty() { verify.selectors.push(Selector::All); }
+                Ok(Command::Verify(verify))
+            }
+            Some("examples") => {
+                let mut selectors = Vec::new();
+                for arg in args {
+                    if arg.starts_with("--") { return Err(CliError::UnknownOption(arg.into())); }
+                    selectors.push(arg.parse().map_err(CliError::Selection)?);
+                }
+                if selectors.is_empty() { selectors.push(Selector::All); }
+                Ok(Command::Examples(selectors))
+            }
+            Some("fetch") => {
+                let mut fetch = Fetch{ selectors: Vec::new(), session: None, base_url: None };
+                while let Some(arg) = args.next() {
+                    match arg {
+                        "--session" => {
+                            let value = args.next().ok_or_else(|| CliError::MissingValue(arg.into()))?;
+                            fetch.session = Some(value.into());
+                        }
+                        "--base-url" => {
+                            let value = args.next().ok_or_else(|| CliError::MissingValue(arg.into()))?;
+                            fetch.base_url = Some(value.into());
+                        }
+                        option if option.starts_with("--") => {
+                            return Err(CliError::UnknownOption(option.into()));
+                        }
+                        selection => {
+                            fetch.selectors.push(selection.parse().map_err(CliError::Selection)?);
+                        }
+                    }
+                }
+                if fetch.selectors.is_empty() { fetch.selectors.push(Selector::All); }
+                Ok(Command::Fetch(fetch))
+            }
+            Some("sonar") => {
+                let mut sonar = Sonar{
+                    input: Source::Default,
+                    mode: Mode::Strict,
+                    window: DEFAULT_WINDOW,
+                    threshold: DEFAULT_THRESHOLD,
+                    width: DEFAULT_WIDTH,
+                };
+                while let Some(arg) = args.next() {
+                    let mut value = || args.next().ok_or_else(|| CliError::MissingValue(arg.into()));
+                    let invalid = |value: &str| CliError::InvalidValue(arg.into(), value.into());
+                    match arg {
+                        "--strict" => sonar.mode = Mode::Strict,
+                        "--lenient" => sonar.mode = Mode::Lenient,
+                        "--input" => sonar.input = Source::from(value()?),
+                        "--window" => {
+                            let value = value()?;
+                            sonar.window = value.parse().ok().filter(|&n| n > 0).ok_or_else(|| invalid(value))?;
+                        }
+                        "--threshold" => {
+                            let value = value()?;
+                            sonar.threshold = value.parse().map_err(|_| invalid(value))?;
+                        }
+                        "--width" => {
+                            let value = value()?;
+                            sonar.width = value.parse().ok().filter(|&n| n > 0).ok_or_else(|| invalid(value))?;
+                        }
+                        option => return Err(CliError::UnknownOption(option.into())),
+                    }
+                }
+                Ok(Command::Sonar(sonar))
+            }
+            Some(arg @ ("help" | "-h" | "--help")) => match args.next() {
+                None => Ok(Command::Help(None)),
+                Some(day) => day
+                    .parse()
+                    .map(|day| Command::Help(Some(day)))
+                    .map_err(|_| CliError::InvalidValue(arg.into(), day.into())),
+            },
+            Some(other) => Err(CliError::UnknownCommand(other.to_string())),
+            None => Err(CliError::MissingCommand),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_command() {
+        assert_eq!(
+            Ok(Command::Run(Run{
+                selectors: vec![Selector::Day(1), Selector::Part(2, 1)],
+                input: Source::Default,
+                mode: Mode::Strict,
+                format: Format::TEXT,
+                params: Vec::new(),
+            })),
+            Command::parse(&["run", "1", "2.1"])
+        );
+        assert_eq!(Ok(Command::Help(None)), Command::parse(&["--help"]));
+        assert_eq!(Ok(Command::Help(Some(6))), Command::parse(&["help", "6"]));
+        assert_eq!(Err(CliError::InvalidValue("help".into(), "x".into())), Command::parse(&["help", "x"]));
+        assert_eq!(Ok(Command::Help(Some(6))), Command::parse(&["run", "6", "--help"]));
+        assert_eq!(Ok(Command::Help(Some(6))), Command::parse(&["run", "-h", "6.2", "--param", "cycle=5"]));
+        assert_eq!(Ok(Command::Help(None)), Command::parse(&["run", "1", "2", "--help"]));
+        assert_eq!(Ok(Command::Help(None)), Command::parse(&["run", "--help"]));
+        assert_eq!(Err(CliError::MissingCommand), Command::parse::<&str>(&[]));
+        assert_eq!(Err(CliError::MissingSelection), Command::parse(&["run"]));
+        assert_eq!(Err(CliError::UnknownCommand("walk".into())), Command::parse(&["walk"]));
+    }
+
+    #[test]
+    fn parse_input_option() {
+        assert_eq!(
+            Ok(Command::Run(Run{
+                selectors: vec![Selector::Day(3)], input: Source::Stdin, mode: Mode::Strict, format: Format::TEXT, params: Vec::new(),
+            })),
+            Command::parse(&["run", "3", "--input", "-"])
+        );
+        assert_eq!(
+            Ok(Command::Run(Run{
+                selectors: vec![Selector::Day(3)], input: Source::File("x".into()), mode: Mode::Strict, format: Format::TEXT, params: Vec::new(),
+            })),
+            Command::parse(&["run", "--input", "x", "3"])
+        );
+        assert_eq!(Err(CliError::MissingValue("--input".into())), Command::parse(&["run", "3", "--input"]));
+        assert_eq!(Err(CliError::UnknownOption("--fast".into())), Command::parse(&["run", "--fast", "3"]));
+    }
+
+    #[test]
+    fn parse_bench_command() {
+        assert_eq!(
+            Ok(Command::Bench(Bench{ selectors: vec![Selector::All], iterations: DEFAULT_ITERATIONS, report: None })),
+            Command::parse(&["bench"])
+        );
+        assert_eq!(
+            Ok(Command::Bench(Bench{ selectors: vec![Selector::Day(6)], iterations: 50, report: Some("b.csv".into()) })),
+            Command::parse(&["bench", "6", "--iterations", "50", "--report", "b.csv"])
+        );
+        assert_eq!(
+            Err(CliError::InvalidValue("--iterations".into(), "0".into())),
+            Command::parse(&["bench", "--iterations", "0"])
+        );
+    }
+
+    #[test]
+    fn parse_verify_command() {
+        assert_eq!(
+            Ok(Command::Verify(Verify{ selectors: vec![Selector::All], record: false })),
+            Command::parse(&["verify"])
+        );
+        assert_eq!(
+            Ok(Command::Verify(Verify{ selectors: vec![Selector::Part(3, 1)], record: true })),
+            Command::parse(&["verify", "--record", "3.1"])
+        );
+    }
+
+    #[test]
+    fn parse_examples_command() {
+        assert_eq!(Ok(Command::Examples(vec![Selector::All])), Command::parse(&["examples"]));
+        assert_eq!(Ok(Command::Examples(vec![Selector::Day(12)])), Command::parse(&["examples", "12"]));
+    }
+
+    #[test]
+    fn parse_fetch_command() {
+        assert_eq!(
+            Ok(Command::Fetch(Fetch{ selectors: vec![Selector::All], session: None, base_url: None })),
+            Command::parse(&["fetch"])
+        );
+        assert_eq!(
+            Ok(Command::Fetch(Fetch{
+                selectors: vec![Selector::Day(4)],
+                session: Some("abc".into()),
+                base_url: Some("http://localhost:8000".into()),
+            })),
+            Command::parse(&["fetch", "--session", "abc", "4", "--base-url", "http://localhost:8000"])
+        );
+        assert_eq!(Err(CliError::MissingValue("--session".into())), Command::parse(&["fetch", "--session"]));
+    }
+
+    #[test]
+    fn parse_sonar_command() {
+        assert_eq!(
+            Ok(Command::Sonar(Sonar{
+                input: Source::Default, mode: Mode::Strict, window: DEFAULT_WINDOW, threshold: DEFAULT_THRESHOLD, width: DEFAULT_WIDTH,
+            })),
+            Command::parse(&["sonar"])
+        );
+        assert_eq!(
+            Ok(Command::Sonar(Sonar{ input: Source::Stdin, mode: Mode::Strict, window: 5, threshold: 0, width: 20 })),
+            Command::parse(&["sonar", "--window", "5", "--input", "-", "--threshold", "0", "--width", "20"])
+        );
+        assert_eq!(Err(CliError::InvalidValue("--window".into(), "0".into())), Command::parse(&["sonar", "--window", "0"]));
+        assert_eq!(Err(CliError::MissingValue("--width".into())), Command::parse(&["sonar", "--width"]));
+        assert_eq!(Err(CliError::UnknownOption("1".into())), Command::parse(&["sonar", "1"]));
+
+        // Flags take no value, the option after them is still read
+        let lenient = Command::parse(&["sonar", "--lenient", "--window", "5"]);
+        assert!(matches!(lenient, Ok(Command::Sonar(Sonar{ mode: Mode::Lenient, window: 5, .. }))));
+        assert_eq!(Err(CliError::UnknownOption("--depth".into())), Command::parse(&["sonar", "--depth", "--lenient"]));
+    }
+
+    #[test]
+    fn parse_format_option() {
+        match Command::parse(&["run", "--format", "json", "all"]) {
+            Ok(Command::Run(run)) => assert_eq!(Format::JSON, run.format),
+            other => panic!("Unexpected {:?}", other),
+        }
+        assert!(matches!(Command::parse(&["run", "--format", "xml", "1"]), Err(CliError::Format(_))));
+    }
+
+    #[test]
+    fn parse_param_option() {
+        match Command::parse(&["run", "11", "--param", "steps=10", "--param", "basins = 2"]) {
+            Ok(Command::Run(run)) => assert_eq!(
+                vec![("steps".to_string(), "10".to_string()), ("basins".to_string(), "2".to_string())],
+                run.params
+            ),
+            other => panic!("Unexpected {:?}", other),
+        }
+        assert_eq!(
+            Err(CliError::Param(ParamError::Malformed("steps".into()))),
+            Command::parse(&["run", "11", "--param", "steps"])
+        );
+    }
+
+    #[test]
+    fn parse_mode_option() {
+        let mode = |args: &[&str]| match Command::parse(args) {
+            Ok(Command::Run(run)) => run.mode,
+            other => panic!("Unexpected {:?}", other),
+        };
+        assert_eq!(Mode::Strict, mode(&["run", "3"]));
+        assert_eq!(Mode::Lenient, mode(&["run", "--lenient", "3"]));
+        assert_eq!(Mode::Strict, mode(&["run", "--lenient", "3", "--strict"]));
+    }
+}
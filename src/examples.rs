@@ -0,0 +1,69 @@
+//! Example inputs with their expected answers, stored as
+//! `data/<day>/examples/<name>.in` and `<name>.expected`. The expected
+//! answers use the format of [`crate::answers`] and may leave parts out.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::answers::{self, Answers};
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Example {
+    pub name: String,
+    pub input: String,
+    pub expected: Answers,
+}
+
+pub fn examples_dir(data_dir: &Path, day: u8) -> PathBuf {
+    data_dir.join(day.to_string()).join("examples")
+}
+
+/// All examples of a day ordered by name, empty if the day has none
+pub fn load(data_dir: &Path, day: u8) -> io::Result<Vec<Example>> {
+    let dir = examples_dir(data_dir, day);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(io::Error::new(e.kind(), format!("Cannot read {}: {}", dir.display(), e))),
+    };
+    let mut inputs: Vec<PathBuf> = entries
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<io::Result<Vec<PathBuf>>>()?
+        .into_iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "in"))
+        .collect();
+    inputs.sort();
+    inputs
+        .into_iter()
+        .map(|path| {
+            let input = fs::read_to_string(&path)
+                .map_err(|e| io::Error::new(e.kind(), format!("Cannot read {}: {}", path.display(), e)))?;
+            Ok(Example {
+                name: path.file_stem().unwrap_or_default().to_string_lossy().into_owned(),
+                input,
+                expected: answers::read(&path.with_extension("expected"))?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::inputs::DATA_DIR;
+
+    #[test]
+    fn load_examples() {
+        let examples = load(Path::new(DATA_DIR), 12).unwrap();
+        let names: Vec<&str> = examples.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(vec!["example1", "example2", "example3"], names);
+        assert_eq!(Some("36"), examples[0].expected.get(2));
+        assert!(examples[0].input.starts_with("start-A"));
+    }
+
+    #[test]
+    fn load_missing_examples() {
+        assert!(load(Path::new("no/such/dir"), 1).unwrap().is_empty());
+    }
+}
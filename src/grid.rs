@@ -0,0 +1,299 @@
+//! A rectangular grid of values, as drawn by many puzzle inputs.
+//!
+//! Positions are [`Vec2`]s with `x` the column and `y` the row: `y` 0 is the
+//! first line of the input and `x` 0 its first character. The shape is
+//! `(rows, cols)`. Positions are signed so that neighbours can be computed
+//! past the edges and then checked with `contains`.
+
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+use crate::parse::{self, Context};
+use crate::solution::ParseError;
+use crate::vec2::Vec2;
+
+pub type Pos = Vec2<isize>;
+
+/// Which cells count as neighbours
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Connectivity {
+    FOUR,   // Orthogonal neighbours only
+    EIGHT,  // Including diagonals
+}
+
+const FOUR: [Pos; 4] = Pos::ORTHOGONAL;
+const EIGHT: [Pos; 8] = Pos::ALL_DIRECTIONS;
+
+impl Connectivity {
+    /// Offsets to the neighbours, in row major order
+    pub fn offsets(self: Self) -> &'static [Pos] {
+        match self {
+            Connectivity::FOUR => &FOUR,
+            Connectivity::EIGHT => &EIGHT,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Grid<T> {
+    data: Vec<T>,   // Row major
+    rows: usize,
+    cols: usize,
+}
+
+impl<T> Grid<T> {
+    /// Grid of `rows` x `cols` cells from row major `data`. Panics if the sizes do not match.
+    pub fn new(rows: usize, cols: usize, data: Vec<T>) -> Self {
+        assert_eq!(rows * cols, data.len(), "Grid data does not match the shape");
+        Grid{ data, rows, cols }
+    }
+
+    /// Panics if the rows differ in length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let cols = rows.first().map_or(0, Vec::len);
+        assert!(rows.iter().all(|row| row.len() == cols), "Grid rows differ in length");
+        Grid{ rows: rows.len(), cols, data: rows.into_iter().flatten().collect() }
+    }
+
+    pub fn filled(rows: usize, cols: usize, value: T) -> Self
+        where T: Clone
+    {
+        Grid{ data: vec![value; rows * cols], rows, cols }
+    }
+
+    pub fn shape(self: &Self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    pub fn len(self: &Self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(self: &Self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn contains(self: &Self, pos: Pos) -> bool {
+        (0..self.rows as isize).contains(&pos.y) &&
+        (0..self.cols as isize).contains(&pos.x)
+    }
+
+    fn index_of(self: &Self, pos: Pos) -> Option<usize> {
+        if !self.contains(pos) { return None; }
+        Some(pos.y as usize * self.cols + pos.x as usize)
+    }
+
+    fn pos_of(self: &Self, idx: usize) -> Pos {
+        Vec2::new((idx % self.cols) as isize, (idx / self.cols) as isize)
+    }
+
+    pub fn get(self: &Self, pos: Pos) -> Option<&T> {
+        self.index_of(pos).map(|i| &self.data[i])
+    }
+
+    pub fn get_mut(self: &mut Self, pos: Pos) -> Option<&mut T> {
+        self.index_of(pos).map(|i| &mut self.data[i])
+    }
+
+    /// All positions in row major order
+    pub fn positions(self: &Self) -> impl Iterator<Item=Pos> + '_ {
+        (0..self.data.len()).map(|i| self.pos_of(i))
+    }
+
+    /// All cells with their position in row major order
+    pub fn iter(self: &Self) -> impl Iterator<Item=(Pos, &T)> {
+        self.data.iter().enumerate().map(|(i, v)| (self.pos_of(i), v))
+    }
+
+    pub fn values(self: &Self) -> std::slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    pub fn values_mut(self: &mut Self) -> std::slice::IterMut<'_, T> {
+        self.data.iter_mut()
+    }
+
+    /// Panics if `row` is out of range
+    pub fn row(self: &Self, row: usize) -> &[T] {
+        &self.data[row * self.cols..(row + 1) * self.cols]
+    }
+
+    pub fn rows(self: &Self) -> impl Iterator<Item=&[T]> {
+        (0..self.rows).map(|r| self.row(r))
+    }
+
+    /// Panics if `col` is out of range
+    pub fn col(self: &Self, col: usize) -> impl Iterator<Item=&T> {
+        assert!(col < self.cols, "Column {} out of range", col);
+        self.data.iter().skip(col).step_by(self.cols)
+    }
+
+    pub fn cols(self: &Self) -> impl Iterator<Item=impl Iterator<Item=&T>> {
+        (0..self.cols).map(|c| self.col(c))
+    }
+
+    /// The neighbours of `pos` that lie within the grid, in row major order
+    pub fn neighbours(self: &Self, pos: Pos, connectivity: Connectivity) -> impl Iterator<Item=Pos> + '_ {
+        connectivity
+            .offsets()
+            .iter()
+            .map(move |&offset| pos + offset)
+            .filter(|&n| self.contains(n))
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(self: &Self, f: F) -> Grid<U> {
+        Grid{ data: self.data.iter().map(f).collect(), rows: self.rows, cols: self.cols }
+    }
+
+    /// Build a grid of the given shape from the value at each position
+    fn build<U, F: FnMut(Pos) -> U>(rows: usize, cols: usize, f: F) -> Grid<U> {
+        let data = (0..rows as isize)
+            .flat_map(|y| (0..cols as isize).map(move |x| Vec2::new(x, y)))
+            .map(f)
+            .collect();
+        Grid{ data, rows, cols }
+    }
+
+    /// Mirror along the main diagonal, `(x, y)` moves to `(y, x)`
+    pub fn transpose(self: &Self) -> Self
+        where T: Clone
+    {
+        Self::build(self.cols, self.rows, |p| self[Vec2::new(p.y, p.x)].clone())
+    }
+
+    /// Rotate by 90 degrees clockwise
+    pub fn rotate(self: &Self) -> Self
+        where T: Clone
+    {
+        let last_row = self.rows as isize - 1;
+        Self::build(self.cols, self.rows, |p| self[Vec2::new(p.y, last_row - p.x)].clone())
+    }
+
+    /// Mirror left to right, reversing each row
+    pub fn flip(self: &Self) -> Self
+        where T: Clone
+    {
+        let last_col = self.cols as isize - 1;
+        Self::build(self.rows, self.cols, |p| self[Vec2::new(last_col - p.x, p.y)].clone())
+    }
+
+    /// Mirror top to bottom, reversing each column
+    pub fn flip_vertical(self: &Self) -> Self
+        where T: Clone
+    {
+        let last_row = self.rows as isize - 1;
+        Self::build(self.rows, self.cols, |p| self[Vec2::new(p.x, last_row - p.y)].clone())
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+    fn index(self: &Self, pos: Pos) -> &T {
+        self.get(pos).unwrap_or_else(|| panic!("Position {:?} is not in the grid", pos))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(self: &mut Self, pos: Pos) -> &mut T {
+        self.get_mut(pos).unwrap_or_else(|| panic!("Position {:?} is not in the grid", pos))
+    }
+}
+
+impl Grid<u8> {
+    /// A grid of single digits, one row per line, see `parse::digit_rows`
+    pub fn digits(s: &str, context: &Context) -> Result<Self, ParseError> {
+        Ok(Grid::from_rows(parse::digit_rows(s, context)?))
+    }
+}
+
+/// A grid of single digits, one row per line, parsed strictly
+impl FromStr for Grid<u8> {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Grid::digits(s, &Context::default())
+    }
+}
+
+/// Each row on its own line, cells printed next to each other
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            for v in row {
+                write!(f, "{}", v)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn grid() -> Grid<u8> {
+        "123\n456".parse().unwrap()
+    }
+
+    #[test]
+    fn parse_digits() {
+        let grid = grid();
+        assert_eq!((2, 3), grid.shape());
+        assert_eq!(Some(&1), grid.get(Vec2::new(0, 0)));
+        assert_eq!(Some(&6), grid.get(Vec2::new(2, 1)));
+        assert_eq!(None, grid.get(Vec2::new(0, 2)));
+        assert_eq!(None, grid.get(Vec2::new(-1, 0)));
+        assert!("12\n3".parse::<Grid<u8>>().is_err());
+    }
+
+    #[test]
+    fn rows_and_cols() {
+        let grid = grid();
+        assert_eq!(vec![&[1, 2, 3][..], &[4, 5, 6][..]], grid.rows().collect::<Vec<_>>());
+        let cols: Vec<Vec<u8>> = grid.cols().map(|c| c.copied().collect()).collect();
+        assert_eq!(vec![vec![1, 4], vec![2, 5], vec![3, 6]], cols);
+        let v = Vec2::new;
+        assert_eq!(vec![v(0, 0), v(1, 0), v(2, 0), v(0, 1)], grid.positions().take(4).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid: Grid<u8> = "123\n456\n789".parse().unwrap();
+        let v = Vec2::new;
+        let four: Vec<Pos> = grid.neighbours(v(0, 1), Connectivity::FOUR).collect();
+        assert_eq!(vec![v(0, 0), v(1, 1), v(0, 2)], four);
+        assert_eq!(4, grid.neighbours(v(1, 1), Connectivity::FOUR).count());
+        assert_eq!(3, grid.neighbours(v(0, 0), Connectivity::EIGHT).count());
+        assert_eq!(5, grid.neighbours(v(1, 0), Connectivity::EIGHT).count());
+        assert_eq!(8, grid.neighbours(v(1, 1), Connectivity::EIGHT).count());
+    }
+
+    #[test]
+    fn transformations() {
+        let grid = grid();
+        assert_eq!("123\n456\n", grid.to_string());
+        assert_eq!("14\n25\n36\n", grid.transpose().to_string());
+        assert_eq!("41\n52\n63\n", grid.rotate().to_string());
+        assert_eq!("321\n654\n", grid.flip().to_string());
+        assert_eq!("456\n123\n", grid.flip_vertical().to_string());
+        assert_eq!(grid, grid.rotate().rotate().rotate().rotate());
+        assert_eq!("FFF\nTTT\n", grid.map(|&v| if v > 3 { 'T' } else { 'F' }).to_string());
+    }
+
+    #[test]
+    fn index_and_modify() {
+        let mut grid = Grid::filled(2, 2, 0);
+        grid[Vec2::new(0, 1)] = 5;
+        *grid.get_mut(Vec2::new(1, 0)).unwrap() += 1;
+        grid.values_mut().for_each(|v| *v *= 2);
+        assert_eq!(vec![0, 2, 10, 0], grid.values().copied().collect::<Vec<_>>());
+        assert_eq!(None, grid.get_mut(Vec2::new(0, 2)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_outside() {
+        let _ = grid()[Vec2::new(3, 0)];
+    }
+}
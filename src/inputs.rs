@@ -0,0 +1,367 @@
+//! Locating, downloading and caching the puzzle inputs.
+//!
+//! Inputs live in `data/<day>/input`. Missing inputs are fetched once from
+//! `<base url>/day/<day>/input` with the session cookie of a logged in
+//! user and never downloaded again while a cached copy exists.
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process;
+
+pub const DATA_DIR: &str = "data";
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com/2021";
+pub const BASE_URL_VAR: &str = "AOC_BASE_URL";
+pub const SESSION_VAR: &str = "AOC_SESSION";
+
+/// Older checkouts split some inputs into two files, which are joined in
+/// this order, separated by an empty line
+const LEGACY_LAYOUTS: &[[&str; 2]] = &[
+    ["numbers", "boards"],
+    ["dots", "folds"],
+    ["polymer", "rules"],
+];
+
+/// Where the puzzle input of a day is read from
+#[derive(Debug, PartialEq, Clone)]
+pub enum Source {
+    Default,        // data/<day>/input
+    File(PathBuf),
+    Stdin,
+}
+
+impl From<&str> for Source {
+    fn from(s: &str) -> Self {
+        match s {
+            "-" => Source::Stdin,
+            path => Source::File(path.into()),
+        }
+    }
+}
+
+pub fn default_path(data_dir: &Path, day: u8) -> PathBuf {
+    data_dir.join(day.to_string()).join("input")
+}
+
+impl Source {
+    pub fn load(self: &Self, day: u8) -> io::Result<String> {
+        match self {
+            Source::Default => read_file(&default_path(Path::new(DATA_DIR), day)),
+            Source::File(path) => read_file(path),
+            Source::Stdin => {
+                let mut input = String::new();
+                io::stdin().read_to_string(&mut input)?;
+                Ok(input)
+            }
+        }
+    }
+}
+
+fn read_file(path: &Path) -> io::Result<String> {
+    fs::read_to_string(path)
+        .map_err(|e| io::Error::new(e.kind(), format!("Cannot read {}: {}", path.display(), e)))
+}
+
+fn write_file(path: &Path, contents: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, contents)
+        .map_err(|e| io::Error::new(e.kind(), format!("Cannot write {}: {}", path.display(), e)))
+}
+
+/// How `fetch` made the input of a day available
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Fetched {
+    CACHED,         // data/<day>/input already existed
+    NORMALISED,     // Joined from a legacy two file layout
+    DOWNLOADED,
+}
+
+impl fmt::Display for Fetched {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(match self {
+            Fetched::CACHED => "CACHED",
+            Fetched::NORMALISED => "NORMALISED",
+            Fetched::DOWNLOADED => "DOWNLOADED",
+        })
+    }
+}
+
+/// Join the files of a legacy layout in `data/<day>/` into the canonical
+/// input file and remove them. Returns false if the day has no such files.
+pub fn normalise(data_dir: &Path, day: u8) -> io::Result<bool> {
+    let dir = data_dir.join(day.to_string());
+    let layout = LEGACY_LAYOUTS
+        .iter()
+        .find(|files| files.iter().all(|f| dir.join(f).is_file()));
+    let [first, second] = match layout {
+        Some(files) => files.map(|f| dir.join(f)),
+        None => return Ok(false),
+    };
+    let joined = format!(
+        "{}\n\n{}\n",
+        read_file(&first)?.trim_end(),
+        read_file(&second)?.trim_matches('\n').trim_end()
+    );
+    write_file(&default_path(data_dir, day), &joined)?;
+    fs::remove_file(first)?;
+    fs::remove_file(second)?;
+    Ok(true)
+}
+
+/// Downloads the inputs that are not cached yet
+#[derive(Debug, PartialEq, Clone)]
+pub struct Fetcher {
+    pub base_url: String,
+    pub session: Option<String>,
+}
+
+impl Fetcher {
+    /// Base url and session from `AOC_BASE_URL` and `AOC_SESSION`
+    pub fn from_env() -> Self {
+        Fetcher{
+            base_url: std::env::var(BASE_URL_VAR).unwrap_or_else(|_| DEFAULT_BASE_URL.to_string()),
+            session: std::env::var(SESSION_VAR).ok().filter(|s| !s.is_empty()),
+        }
+    }
+
+    pub fn url(self: &Self, day: u8) -> String {
+        format!("{}/day/{}/input", self.base_url.trim_end_matches('/'), day)
+    }
+
+    /// Make `data/<day>/input` available. An existing file is never
+    /// replaced, legacy layouts are normalised and only if neither exists
+    /// the input is downloaded, which requires a session token.
+    pub fn fetch(self: &Self, data_dir: &Path, day: u8) -> io::Result<Fetched> {
+        let path = default_path(data_dir, day);
+        if path.is_file() { return Ok(Fetched::CACHED); }
+        if normalise(data_dir, day)? { return Ok(Fetched::NORMALISED); }
+
+        let session = self.session.as_deref().ok_or_else(|| io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("No session token to download day {}, set {}", day, SESSION_VAR),
+        ))?;
+        let input = download(&self.url(day), session)?;
+        if input.trim().is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Empty input for day {}", day)));
+        }
+        write_file(&path, &input)?;
+        Ok(Fetched::DOWNLOADED)
+    }
+}
+
+fn download(url: &str, session: &str) -> io::Result<String> {
+    if let Some(rest) = url.strip_prefix("http://") {
+        http_get(rest, session).map_err(|e| io::Error::new(e.kind(), format!("GET {}: {}", url, e)))
+    }
+    else if url.starts_with("https://") {
+        curl_get(url, session)
+    }
+    else {
+        Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Unsupported url '{}'", url)))
+    }
+}
+
+/// A plain HTTP/1.0 GET of `host[:port]/path`, so that the response is
+/// neither chunked nor kept alive
+fn http_get(host_and_path: &str, session: &str) -> io::Result<String> {
+    let (host, path) = match host_and_path.find('/') {
+        Some(i) => host_and_path.split_at(i),
+        None => (host_and_path, "/"),
+    };
+    let address = if host.contains(':') { host.to_string() } else { format!("{}:80", host) };
+    let mut stream = TcpStream::connect(address)?;
+    write!(
+        stream,
+        "GET {} HTTP/1.0\r\nHost: {}\r\nCookie: session={}\r\nUser-Agent: advent_of_code input fetcher\r\n\r\n",
+        path, host, session
+    )?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| invalid("Malformed HTTP response".to_string()))?;
+    let status = head.lines().next().unwrap_or_default();
+    match status.split_whitespace().nth(1) {
+        Some("200") => Ok(body.to_string()),
+        _ => Err(invalid(format!("Unexpected response '{}'", status))),
+    }
+}
+
+/// HTTPS is left to curl, std has no TLS. The session is handed over as a
+/// config file on stdin, arguments can be read by other local users.
+fn curl_get(url: &str, session: &str) -> io::Result<String> {
+    let cannot_run = |e: io::Error| io::Error::new(e.kind(), format!("Cannot run curl for {}: {}", url, e));
+    let mut curl = process::Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--config", "-"])
+        .arg(url)
+        .stdin(process::Stdio::piped())
+        .stdout(process::Stdio::piped())
+        .stderr(process::Stdio::piped())
+        .spawn()
+        .map_err(cannot_run)?;
+    // Dropping stdin closes it, so that curl stops reading its config
+    curl.stdin.take().unwrap().write_all(curl_config(session).as_bytes()).map_err(cannot_run)?;
+    let output = curl.wait_with_output().map_err(cannot_run)?;
+    if !output.status.success() {
+        let err = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!("GET {}: {}", url, err.trim())));
+    }
+    String::from_utf8(output.stdout).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// A curl config sending the session cookie
+fn curl_config(session: &str) -> String {
+    format!("cookie = \"session={}\"\n", session.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn source_from_arg() {
+        assert_eq!(Source::Stdin, Source::from("-"));
+        assert_eq!(Source::File("my/input".into()), Source::from("my/input"));
+    }
+
+    #[test]
+    fn default_input_path() {
+        assert_eq!(PathBuf::from("data/9/input"), default_path(Path::new(DATA_DIR), 9));
+    }
+
+    #[test]
+    fn load_default_input() {
+        let input = Source::Default.load(1).unwrap();
+        assert!(!input.is_empty());
+    }
+
+    /// An empty scratch directory for one test, removed again when dropped
+    struct Scratch(PathBuf);
+
+    impl std::ops::Deref for Scratch {
+        type Target = Path;
+        fn deref(self: &Self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(self: &mut Self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn scratch(name: &str) -> Scratch {
+        let dir = std::env::temp_dir().join(format!("aoc-inputs-{}-{}", process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Scratch(dir)
+    }
+
+    /// Serve one canned response per connection on a local port, handing
+    /// back the requests received
+    fn mock_server(responses: Vec<&'static str>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}/2021", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            responses
+                .into_iter()
+                .map(|response| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut request = Vec::new();
+                    let mut buf = [0; 1024];
+                    while !request.ends_with(b"\r\n\r\n") {
+                        let n = stream.read(&mut buf).unwrap();
+                        if n == 0 { break; }
+                        request.extend_from_slice(&buf[..n]);
+                    }
+                    stream.write_all(response.as_bytes()).unwrap();
+                    String::from_utf8(request).unwrap()
+                })
+                .collect()
+        });
+        (base_url, server)
+    }
+
+    fn fetcher(base_url: &str) -> Fetcher {
+        Fetcher{ base_url: base_url.to_string(), session: Some("53cr3t".to_string()) }
+    }
+
+    #[test]
+    fn download_and_cache() {
+        let data_dir = scratch("download");
+        let (base_url, server) = mock_server(vec!["HTTP/1.1 200 OK\r\nContent-Length: 8\r\n\r\n199\n200\n"]);
+        let fetcher = fetcher(&base_url);
+
+        assert_eq!(Fetched::DOWNLOADED, fetcher.fetch(&data_dir, 1).unwrap());
+        assert_eq!("199\n200\n", fs::read_to_string(default_path(&data_dir, 1)).unwrap());
+        // The server answers only once, so this must come from the cache
+        assert_eq!(Fetched::CACHED, fetcher.fetch(&data_dir, 1).unwrap());
+
+        let requests = server.join().unwrap();
+        assert_eq!(1, requests.len());
+        assert!(requests[0].starts_with("GET /2021/day/1/input HTTP/1.0\r\n"));
+        assert!(requests[0].contains("\r\nCookie: session=53cr3t\r\n"));
+    }
+
+    #[test]
+    fn download_error() {
+        let data_dir = scratch("error");
+        let (base_url, server) = mock_server(vec!["HTTP/1.1 404 Not Found\r\n\r\nPlease don't repeatedly request this endpoint"]);
+
+        let err = fetcher(&base_url).fetch(&data_dir, 25).unwrap_err();
+        assert!(err.to_string().contains("404 Not Found"));
+        assert!(!default_path(&data_dir, 25).exists());
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn download_needs_session() {
+        let data_dir = scratch("session");
+        let fetcher = Fetcher{ base_url: "http://127.0.0.1:9".to_string(), session: None };
+        let err = fetcher.fetch(&data_dir, 2).unwrap_err();
+        assert_eq!(io::ErrorKind::PermissionDenied, err.kind());
+        assert!(err.to_string().contains(SESSION_VAR));
+    }
+
+    #[test]
+    fn normalise_legacy_layout() {
+        let data_dir = scratch("legacy");
+        let dir = data_dir.join("4");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("numbers"), "7,4,9\n").unwrap();
+        fs::write(dir.join("boards"), "\n1 2\n3 4\n").unwrap();
+
+        let fetcher = Fetcher{ base_url: "http://127.0.0.1:9".to_string(), session: None };
+        assert_eq!(Fetched::NORMALISED, fetcher.fetch(&data_dir, 4).unwrap());
+        assert_eq!("7,4,9\n\n1 2\n3 4\n", fs::read_to_string(default_path(&data_dir, 4)).unwrap());
+        assert!(!dir.join("numbers").exists() && !dir.join("boards").exists());
+        assert!(!normalise(&data_dir, 4).unwrap());
+    }
+
+    #[test]
+    fn fetch_url() {
+        assert_eq!("http://localhost:8000/day/6/input", fetcher("http://localhost:8000/").url(6));
+        assert_eq!("https://adventofcode.com/2021/day/6/input", fetcher(DEFAULT_BASE_URL).url(6));
+    }
+
+    #[test]
+    fn curl_session_config() {
+        assert_eq!("cookie = \"session=53cr3t\"\n", curl_config("53cr3t"));
+        assert_eq!("cookie = \"session=a\\\"b\\\\\"\n", curl_config("a\"b\\"));
+    }
+
+    #[test]
+    fn load_missing_input() {
+        let err = Source::File("no/such/file".into()).load(1).unwrap_err();
+        assert_eq!(io::ErrorKind::NotFound, err.kind());
+        assert!(err.to_string().contains("no/such/file"));
+    }
+}
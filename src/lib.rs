@@ -0,0 +1,42 @@
+//! Solutions to the Advent of Code 2021 puzzles.
+//!
+//! Every day lives in its own `puzzle_N` module and exposes a `Puzzle` type
+//! implementing [`solution::Solution`], next to the domain types it is built
+//! from (`puzzle_4::Board`, `puzzle_9::Heightmap`, `puzzle_11::OctoMap`,
+//! `puzzle_12::CaveMap`, `puzzle_14::Polymer`, ...). Shared building blocks
+//! such as [`grid::Grid`] and [`vec2::Vec2`] live in their own modules. The
+//! [`registry`] lists all days for runners, [`inputs`] locates and downloads
+//! the puzzle input of a day, [`params`] holds the tunable constants of the
+//! days and [`answers`] and [`examples`] hold the known good answers.
+//! [`sonar`] characterises the day 1 depths beyond the puzzle answers.
+
+// House style: `self: &Self` receivers and SHOUTY enum variants
+#![allow(clippy::needless_arbitrary_self_type, clippy::upper_case_acronyms)]
+
+pub mod puzzle_1;
+pub mod puzzle_2;
+pub mod puzzle_3;
+pub mod puzzle_4;
+pub mod puzzle_5;
+pub mod puzzle_6;
+pub mod puzzle_7;
+pub mod puzzle_8;
+pub mod puzzle_9;
+pub mod puzzle_10;
+pub mod puzzle_11;
+pub mod puzzle_12;
+pub mod puzzle_13;
+pub mod puzzle_14;
+pub mod answers;
+pub mod bench;
+pub mod examples;
+pub mod grid;
+pub mod inputs;
+pub mod ocr;
+pub mod output;
+pub mod params;
+pub mod parse;
+pub mod registry;
+pub mod solution;
+pub mod sonar;
+pub mod vec2;
@@ -1,74 +1,360 @@
-mod puzzle_1;
-mod puzzle_2;
-mod puzzle_3; 
-mod puzzle_4;
-mod puzzle_5;
-mod puzzle_6;
-mod puzzle_7;
-mod puzzle_8;
-mod puzzle_9;
-mod puzzle_10;
-mod puzzle_11;
-mod puzzle_12;
-mod puzzle_13;
-mod puzzle_14;
+// House style: `self: &Self` receivers
+#![allow(clippy::needless_arbitrary_self_type)]
+
+mod cli;
+
+use std::fs;
+use std::path::Path;
+use std::process::exit;
+use std::time::{Duration, Instant};
+
+use advent_of_code::answers::{self, Verdict};
+use advent_of_code::bench;
+use advent_of_code::examples;
+use advent_of_code::inputs::{Fetcher, Source, DATA_DIR};
+use advent_of_code::output::{self, Format, Record};
+use advent_of_code::params::{self, Values};
+use advent_of_code::parse::{Context, ParseError};
+use advent_of_code::registry::{self, Day, Selector, Task};
+use advent_of_code::sonar::{self, Sweep};
+use cli::{Bench, Command, Fetch, Run, Sonar, Verify};
+
+/// Warn about the lines skipped in lenient mode
+fn report_skipped(day: u8, skipped: &[ParseError]) {
+    if skipped.is_empty() { return; }
+    let lines: Vec<String> = skipped
+        .iter()
+        .map(|e| e.line.map(|l| l.to_string()).unwrap_or_else(|| "?".into()))
+        .collect();
+    eprintln!("Warning: Day {}: skipped {} malformed line(s) or item(s) at line(s): {}", day, skipped.len(), lines.join(", "));
+    for e in skipped {
+        eprintln!("{}", e.clone().day(day));
+    }
+}
+
+/// Resolve the selection, exiting on unknown days or parts
+fn select(selectors: &[Selector]) -> Vec<Task> {
+    match registry::select(selectors) {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            exit(1);
+        }
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Print a record in the given format. Failed parts are only reported on stderr in text mode.
+fn emit(format: Format, day: &Day, values: &Values, record: &Record) {
+    match format {
+        Format::TEXT => {
+            if let Ok(answer) = &record.answer {
+                println!("{}", day.format(record.part, answer, values));
+            }
+        }
+        Format::JSON => println!("{}", record.to_json()),
+        Format::TSV => println!("{}", record.to_tsv()),
+    }
+}
+
+/// Solve the selected puzzles, returns false if any day failed
+fn run(run: Run) -> bool {
+    let tasks = select(&run.selectors);
+    if run.input != Source::Default && tasks.len() > 1 {
+        eprintln!("Error: --input can only be used when a single day is selected");
+        exit(2);
+    }
+    if let Err(e) = params::check_known(tasks.iter().flat_map(|t| t.day.params), &run.params) {
+        eprintln!("Error: {}", e);
+        exit(2);
+    }
+    let values: Vec<Values> = tasks
+        .iter()
+        .map(|t| params::resolve(t.day.params, &run.params))
+        .collect::<Result<_, _>>()
+        .unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            exit(2);
+        });
+    if run.format == Format::TSV {
+        println!("{}", output::TSV_HEADER);
+    }
+    let mut ok = true;
+    for (task, values) in tasks.into_iter().zip(values) {
+        ok &= run_task(&run, task, &values);
+    }
+    ok
+}
+
+/// Solve the parts of one day, returns false if it failed to load or parse
+/// or a part could not be solved
+fn run_task(run: &Run, task: Task, values: &Values) -> bool {
+    let day = task.day;
+    let mut parse_time = Duration::ZERO;
+    let parsed = run.input
+        .load(day.day)
+        .map_err(|e| format!("Day {}: {}", day.day, e))
+        .and_then(|input| {
+            let start = Instant::now();
+            let context = Context::new(run.mode);
+            let parsed = (day.parse)(&input, values, &context);
+            parse_time = start.elapsed();
+            report_skipped(day.day, &context.into_skipped());
+            parsed.map_err(|e| e.day(day.day).to_string())
+        });
+    if let Err(e) = &parsed {
+        eprintln!("Error: {}", e);
+    }
+    let mut ok = parsed.is_ok();
+    for part in task.parts {
+        let start = Instant::now();
+        let answer = parsed.as_ref().map_err(Clone::clone).and_then(|parsed| {
+            parsed.solve(part).map_err(|e| format!("Day {}.{}: {}", day.day, part, e))
+        });
+        if let (Ok(_), Err(e)) = (&parsed, &answer) {
+            eprintln!("Error: {}", e);
+            ok = false;
+        }
+        let record = Record{
+            day: day.day,
+            part,
+            answer,
+            parse_ms: millis(parse_time),
+            solve_ms: millis(start.elapsed()),
+        };
+        emit(run.format, day, values, &record);
+    }
+    ok
+}
+
+/// Time the selected puzzles and optionally write a report, returns false if any day failed
+fn bench(options: Bench) -> bool {
+    let mut ok = true;
+    let mut timings = Vec::new();
+    for task in select(&options.selectors) {
+        let day = task.day;
+        let result = Source::Default
+            .load(day.day)
+            .map_err(|e| format!("Day {}: {}", day.day, e))
+            .and_then(|input| {
+                bench::bench_day(day, &task.parts, &input, options.iterations)
+                    .map_err(|e| e.day(day.day).to_string())
+            });
+        match result {
+            Ok(day_timings) => timings.extend(day_timings),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                ok = false;
+            }
+        }
+    }
+    print!("{}", bench::table(&timings));
+    if let Some(path) = options.report {
+        let report = match path.extension().and_then(|e| e.to_str()) {
+            Some("csv") => bench::to_csv(&timings, options.iterations),
+            _ => bench::to_json(&timings, options.iterations),
+        };
+        if let Err(e) = fs::write(&path, report) {
+            eprintln!("Error: Cannot write {}: {}", path.display(), e);
+            ok = false;
+        }
+    }
+    ok
+}
+
+/// Check the selected answers against data/<day>/answers, recording them
+/// instead with `--record`. Returns false if any answer differs or a part
+/// cannot be solved, those are never recorded.
+fn verify(options: Verify) -> bool {
+    let data_dir = Path::new(DATA_DIR);
+    let mut ok = true;
+    for task in select(&options.selectors) {
+        let day = task.day;
+        let checked = Source::Default
+            .load(day.day)
+            .and_then(|input| Ok((input, answers::load(data_dir, day.day)?)))
+            .map_err(|e| format!("Day {}: {}", day.day, e))
+            .and_then(|(input, recorded)| {
+                let parsed = (day.parse)(&input, &Values::new(), &Context::default()).map_err(|e| e.day(day.day).to_string())?;
+                Ok((parsed, recorded))
+            });
+        let (parsed, mut recorded) = match checked {
+            Ok(checked) => checked,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                ok = false;
+                continue;
+            }
+        };
+        for &part in &task.parts {
+            let answer = match parsed.solve(part) {
+                Ok(answer) => answer,
+                Err(e) => {
+                    println!("ERROR    {}.{}: {}", day.day, part, e);
+                    ok = false;
+                    continue;
+                }
+            };
+            match recorded.verify(part, &answer) {
+                Verdict::PASS => println!("PASS     {}.{}", day.day, part),
+                Verdict::MISSING => println!("MISSING  {}.{}", day.day, part),
+                Verdict::FAIL(expected) => {
+                    println!("FAIL     {}.{}: expected {}, got {}", day.day, part, expected, answer);
+                    ok = ok && options.record;
+                }
+            }
+            recorded.insert(part, &answer);
+        }
+        if options.record {
+            if let Err(e) = answers::save(data_dir, day.day, &recorded) {
+                eprintln!("Error: {}", e);
+                ok = false;
+            }
+        }
+    }
+    ok
+}
+
+/// Solve the examples of the selected days and check their expected
+/// answers, returns false if any example fails
+fn examples(selectors: &[Selector]) -> bool {
+    let mut ok = true;
+    for task in select(selectors) {
+        let day = task.day;
+        let examples = match examples::load(Path::new(DATA_DIR), day.day) {
+            Ok(examples) => examples,
+            Err(e) => {
+                eprintln!("Error: Day {}: {}", day.day, e);
+                ok = false;
+                continue;
+            }
+        };
+        if examples.is_empty() {
+            println!("MISSING  {}: no examples", day.day);
+        }
+        for example in examples {
+            let parsed = match (day.parse)(&example.input, &Values::new(), &Context::default()) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    eprintln!("Error: Example {}: {}", example.name, e.day(day.day));
+                    ok = false;
+                    continue;
+                }
+            };
+            for &part in &task.parts {
+                let answer = match parsed.solve(part) {
+                    Ok(answer) => answer,
+                    Err(e) => {
+                        println!("ERROR    {}.{} {}: {}", day.day, part, example.name, e);
+                        ok = false;
+                        continue;
+                    }
+                };
+                match example.expected.verify(part, &answer) {
+                    Verdict::PASS => println!("PASS     {}.{} {}", day.day, part, example.name),
+                    Verdict::MISSING => println!("MISSING  {}.{} {}", day.day, part, example.name),
+                    Verdict::FAIL(expected) => {
+                        println!("FAIL     {}.{} {}: expected {}, got {}", day.day, part, example.name, expected, answer);
+                        ok = false;
+                    }
+                }
+            }
+        }
+    }
+    ok
+}
+
+/// Make the inputs of the selected days available in data/<day>/input,
+/// returns false if any of them could not be fetched
+fn fetch(options: Fetch) -> bool {
+    let mut fetcher = Fetcher::from_env();
+    if let Some(session) = options.session { fetcher.session = Some(session); }
+    if let Some(base_url) = options.base_url { fetcher.base_url = base_url; }
+
+    let mut ok = true;
+    for task in select(&options.selectors) {
+        match fetcher.fetch(Path::new(DATA_DIR), task.day.day) {
+            Ok(fetched) => println!("{:<10} {}", fetched, task.day.day),
+            Err(e) => {
+                eprintln!("Error: Day {}: {}", task.day.day, e);
+                ok = false;
+            }
+        }
+    }
+    ok
+}
+
+/// Summarise the day 1 depths, returns false if they cannot be read
+fn sonar(options: Sonar) -> bool {
+    let context = Context::new(options.mode);
+    let sweep = options.input
+        .load(1)
+        .map_err(|e| format!("Day 1: {}", e))
+        .and_then(|input| Sweep::parse(&input, &context).map_err(|e| e.day(1).to_string()));
+    report_skipped(1, &context.into_skipped());
+    let sweep = match sweep {
+        Ok(sweep) => sweep,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return false;
+        }
+    };
+    let summary = match sweep.summary() {
+        Some(summary) => summary,
+        None => {
+            println!("No depths.");
+            return true;
+        }
+    };
+    println!("{}", summary);
+    println!("depth            {}", sweep.sparkline(options.width));
+    let average = sweep.moving_average(options.window);
+    println!("{:<16} {}", format!("average of {}", options.window), sonar::sparkline(&average, options.width));
+    let jumps = sweep.jumps(options.threshold);
+    println!("{} jump(s) larger than {}", jumps.len(), options.threshold);
+    for jump in jumps {
+        println!("    {}", jump);
+    }
+    true
+}
+
+/// List the parameters of a day
+fn help(day: u8) -> bool {
+    for task in select(&[Selector::Day(day)]) {
+        if task.day.params.is_empty() {
+            println!("Day {} has no parameters.", day);
+            continue;
+        }
+        println!("Parameters of day {} (set with --param <key>=<value>):", day);
+        for param in task.day.params {
+            println!("    {}", param);
+        }
+    }
+    true
+}
 
 fn main() {
-    println!("Puzzle 1.1: There are {} instances of increased depth.", 
-             puzzle_1::count_increasing_depth());
-    println!("Puzzle 1.2: There are {} instances of increased depth using a sliding window of size 3.", 
-             puzzle_1::count_sliding_window_increasing_depth());
-    println!("Puzzle 2.1: The final posision metric of the sub (horizontal x depth) is {}.",
-             puzzle_2::compute_position());
-    println!("Puzzle 2.2: The final posision metric of the sub (horizontal x depth) using the aimed algorthim is {}.",
-             puzzle_2::compute_position_aimed());
-    println!("Puzzle 3.1: The power consumption (gamma x epsilon) is {}",
-             puzzle_3::calculate_power_consumption());
-    println!("Puzzle 3.2: The life rating (oxygen x co2 rating) is {}",
-             puzzle_3::calculate_life_rating());
-    println!("Puzzle 4.1: The winning bingo board has the score {}",
-             puzzle_4::winning_bingo_score());
-    println!("Puzzle 4.2: The loosing bingo board has the score {}",
-             puzzle_4::loosing_bingo_score());
-    println!("Puzzle 5.1: The number of points at which straight lines overlap is {}",
-             puzzle_5::overlapping_straight_line_count());
-    println!("Puzzle 5.2: The number of points at which straight and diagonal lines overlap is {}",
-             puzzle_5::overlapping_diagonal_line_count());
-    println!("Puzzle 6.1: After 80 days the number of lanternfish is {}",
-             puzzle_6::lanternfish_population(80));
-    println!("Puzzle 6.2: After 256 days the number of lanternfish is {}",
-             puzzle_6::lanternfish_population(256));
-    println!("Puzzle 7.1: The fuel cost to align the crabs is {}",
-             puzzle_7::align_crabs_cost());
-    println!("Puzzle 7.2: The fuel cost to align the crabs with linearly increasing fuel cost is {}",
-             puzzle_7::align_crabs_cost_linear());
-    println!("Puzzle 8.1: The number of digitis that use unique segments (1,4,7,8) is {}",
-             puzzle_8::count_digits_1478());
-    println!("Puzzle 8.2: The sum of all decoded seven segment digits is {}",
-             puzzle_8::sum_all_decoded_outputs());
-    println!("Puzzle 9.1: The sum of all risk levels is {}",
-             puzzle_9::sum_risk_levels());
-    println!("Puzzle 9.2: The basin risk level is {}",
-             puzzle_9::basin_risk_level());
-    println!("Puzzle 10.1: The syntax error score is {}",
-             puzzle_10::syntax_error_score());
-    println!("Puzzle 10.2: The middle complettion score is {}",
-             puzzle_10::completion_score());
-    println!("Puzzle 11.1: After 100 steps, the number of octupus flashes is {}",
-             puzzle_11::octopus_flashes());
-    println!("Puzzle 11.2: All octupus flashes are in sync after {} steps.",
-             puzzle_11::octopus_sync_flashes());
-    println!("Puzzle 12.1: The number of paths from start to end is {}", 
-             puzzle_12::number_of_paths());
-    println!("Puzzle 12.2: The number of paths from start to end with one double visit is {}", 
-             puzzle_12::number_of_paths_double_visit());
-    println!("Puzzle 13.1: After one fold the number of visible dots is {}",
-             puzzle_13::dots_after_one_fold());
-    println!("Puzzle 13.2: The infrared system activation code is:\n{}",
-             puzzle_13::fold_and_format_dots());
-    println!("Puzzle 14.1: The difference between the number of the most and least common polymer element after 10 steps is {}",
-             puzzle_14::polymer_index(10));
-    println!("Puzzle 14.2: The difference between the number of the most and least common polymer element after 40 steps is {}",
-             puzzle_14::polymer_index(40));
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = match Command::parse(&args) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("Error: {}\n\n{}", e, cli::USAGE);
+            exit(2);
+        }
+    };
 
+    let ok = match command {
+        Command::Help(None) => { println!("{}", cli::USAGE); true }
+        Command::Help(Some(day)) => help(day),
+        Command::Run(options) => run(options),
+        Command::Bench(options) => bench(options),
+        Command::Verify(options) => verify(options),
+        Command::Examples(selectors) => examples(&selectors),
+        Command::Fetch(options) => fetch(options),
+        Command::Sonar(options) => sonar(options),
+    };
+    if !ok { exit(1); }
 }
@@ -0,0 +1,84 @@
+//! Reading the letters drawn in the 4x6 pixel font of the puzzles, such as
+//! the activation code of day 13. Letters are 4 columns wide and separated
+//! by one empty column, `#` is a lit pixel and anything else is dark.
+
+const HEIGHT: usize = 6;
+const WIDTH: usize = 4;
+
+const GLYPHS: &[(char, [&str; HEIGHT])] = &[
+    ('A', [" ## ", "#  #", "#  #", "####", "#  #", "#  #"]),
+    ('B', ["### ", "#  #", "### ", "#  #", "#  #", "### "]),
+    ('C', [" ## ", "#  #", "#   ", "#   ", "#  #", " ## "]),
+    ('E', ["####", "#   ", "### ", "#   ", "#   ", "####"]),
+    ('F', ["####", "#   ", "### ", "#   ", "#   ", "#   "]),
+    ('G', [" ## ", "#  #", "#   ", "# ##", "#  #", " ###"]),
+    ('H', ["#  #", "#  #", "####", "#  #", "#  #", "#  #"]),
+    ('I', [" ###", "  # ", "  # ", "  # ", "  # ", " ###"]),
+    ('J', ["  ##", "   #", "   #", "   #", "#  #", " ## "]),
+    ('K', ["#  #", "# # ", "##  ", "# # ", "# # ", "#  #"]),
+    ('L', ["#   ", "#   ", "#   ", "#   ", "#   ", "####"]),
+    ('O', [" ## ", "#  #", "#  #", "#  #", "#  #", " ## "]),
+    ('P', ["### ", "#  #", "#  #", "### ", "#   ", "#   "]),
+    ('R', ["### ", "#  #", "#  #", "### ", "# # ", "#  #"]),
+    ('S', [" ###", "#   ", "#   ", " ## ", "   #", "### "]),
+    ('U', ["#  #", "#  #", "#  #", "#  #", "#  #", " ## "]),
+    ('Z', ["####", "   #", "  # ", " #  ", "#   ", "####"]),
+];
+
+/// The pixels of column `col` to `col + WIDTH` of a row, as `#` and ` `
+fn cell(row: &[bool], col: usize) -> String {
+    (col..col + WIDTH)
+        .map(|c| if row.get(c).copied().unwrap_or(false) { '#' } else { ' ' })
+        .collect()
+}
+
+/// Decode the letters drawn in `text`, `None` unless it is exactly six rows
+/// of known letters
+pub fn decode(text: &str) -> Option<String> {
+    let rows: Vec<Vec<bool>> = text
+        .trim_end_matches('\n')
+        .split('\n')
+        .map(|row| row.chars().map(|c| c == '#').collect())
+        .collect();
+    if rows.len() != HEIGHT { return None; }
+    let width = rows.iter().map(|row| row.iter().rposition(|&lit| lit).map_or(0, |c| c + 1)).max()?;
+    if width == 0 { return None; }
+
+    (0..width)
+        .step_by(WIDTH + 1)
+        .map(|col| {
+            let glyph: Vec<String> = rows.iter().map(|row| cell(row, col)).collect();
+            GLYPHS
+                .iter()
+                .find(|(_, pixels)| pixels.iter().zip(&glyph).all(|(p, g)| p == g))
+                .map(|(letter, _)| *letter)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CODE: &str = "\
+#  # #  # #  #   ##  ##   ##    ## ####
+#  # # #  #  #    # #  # #  #    #    #
+#### ##   #  #    # #    #  #    #   #
+#  # # #  #  #    # # ## ####    #  #
+#  # # #  #  # #  # #  # #  # #  # #
+#  # #  #  ##   ##   ### #  #  ##  ####
+";
+
+    #[test]
+    fn decode_code() {
+        assert_eq!(Some("HKUJGAJZ".to_string()), decode(CODE));
+        assert_eq!(Some("HKUJGAJZ".to_string()), decode(&CODE.replace(' ', ".")));
+    }
+
+    #[test]
+    fn decode_unknown() {
+        assert_eq!(None, decode("#####\n#   #\n#   #\n#   #\n#####\n"));
+        assert_eq!(None, decode(&CODE.replacen('#', " ", 1)));
+        assert_eq!(None, decode(""));
+    }
+}
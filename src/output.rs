@@ -0,0 +1,163 @@
+//! Machine readable runner output: one record per solved part, as JSON
+//! lines or tab separated values.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::ocr;
+use crate::solution::Answer;
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Format {
+    #[default]
+    TEXT,   // The sentences of the registry
+    JSON,
+    TSV,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseFormatError(String);
+
+impl fmt::Display for ParseFormatError {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown format '{}', expected text, json or tsv", self.0)
+    }
+}
+
+impl FromStr for Format {
+    type Err = ParseFormatError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::TEXT),
+            "json" => Ok(Format::JSON),
+            "tsv" => Ok(Format::TSV),
+            other => Err(ParseFormatError(other.to_string())),
+        }
+    }
+}
+
+/// The outcome of one part. `answer` holds the error instead if the day
+/// failed to load or parse, or the part could not be solved.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Record {
+    pub day: u8,
+    pub part: u8,
+    pub answer: Result<Answer, String>,
+    pub parse_ms: f64,
+    pub solve_ms: f64,
+}
+
+pub const TSV_HEADER: &str = "day\tpart\tanswer\tparse_ms\tsolve_ms\tstatus";
+
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+impl Record {
+    fn status(self: &Self) -> &'static str {
+        if self.answer.is_ok() { "ok" } else { "error" }
+    }
+
+    /// A single line JSON object. Text answers are drawings: `answer` holds the
+    /// decoded letters if they can be read (the raw text otherwise), `grid`
+    /// the rows of the drawing. Failed parts have a null `answer` and an `error`.
+    pub fn to_json(self: &Self) -> String {
+        let answer = match &self.answer {
+            Err(e) => format!("null, \"error\": {}", json_string(e)),
+            Ok(Answer::Integer(v)) => v.to_string(),
+            Ok(Answer::Unsigned(v)) => v.to_string(),
+            Ok(Answer::Text(text)) => {
+                let rows: Vec<String> = text.trim_end_matches('\n').split('\n').map(json_string).collect();
+                let letters = ocr::decode(text);
+                format!(
+                    "{}, \"grid\": [{}], \"letters\": {}",
+                    json_string(letters.as_deref().unwrap_or(text)),
+                    rows.join(", "),
+                    letters.as_deref().map_or("null".to_string(), json_string)
+                )
+            }
+        };
+        format!(
+            "{{\"day\": {}, \"part\": {}, \"answer\": {}, \"parse_ms\": {:.3}, \"solve_ms\": {:.3}, \"status\": \"{}\"}}",
+            self.day, self.part, answer, self.parse_ms, self.solve_ms, self.status()
+        )
+    }
+
+    /// A line matching `TSV_HEADER`. Text answers are given as their decoded
+    /// letters, or with newlines escaped as `\n` if they cannot be read.
+    pub fn to_tsv(self: &Self) -> String {
+        let answer = match &self.answer {
+            Err(_) => String::new(),
+            Ok(Answer::Integer(v)) => v.to_string(),
+            Ok(Answer::Unsigned(v)) => v.to_string(),
+            Ok(Answer::Text(text)) => ocr::decode(text)
+                .unwrap_or_else(|| text.trim_end_matches('\n').replace('\n', "\\n").replace('\t', " ")),
+        };
+        format!("{}\t{}\t{}\t{:.3}\t{:.3}\t{}", self.day, self.part, answer, self.parse_ms, self.solve_ms, self.status())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record(answer: Result<Answer, String>) -> Record {
+        Record{ day: 6, part: 2, answer, parse_ms: 0.25, solve_ms: 1.5 }
+    }
+
+    #[test]
+    fn parse_format() {
+        assert_eq!(Ok(Format::JSON), "json".parse());
+        assert_eq!(Ok(Format::TSV), "tsv".parse());
+        assert!("xml".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn integer_record() {
+        let record = record(Ok(Answer::Integer(26984457539)));
+        assert_eq!(
+            "{\"day\": 6, \"part\": 2, \"answer\": 26984457539, \"parse_ms\": 0.250, \"solve_ms\": 1.500, \"status\": \"ok\"}",
+            record.to_json()
+        );
+        assert_eq!("6\t2\t26984457539\t0.250\t1.500\tok", record.to_tsv());
+    }
+
+    #[test]
+    fn wide_integer_record() {
+        let record = record(Ok(Answer::from(u128::MAX)));
+        assert!(record.to_json().contains("\"answer\": 340282366920938463463374607431768211455, \"parse_ms\""));
+        assert_eq!("6\t2\t340282366920938463463374607431768211455\t0.250\t1.500\tok", record.to_tsv());
+    }
+
+    #[test]
+    fn text_record() {
+        let record = record(Ok(Answer::Text("#\"\n #\n".into())));
+        assert_eq!(
+            "{\"day\": 6, \"part\": 2, \"answer\": \"#\\\"\\n #\\n\", \"grid\": [\"#\\\"\", \" #\"], \"letters\": null, \
+             \"parse_ms\": 0.250, \"solve_ms\": 1.500, \"status\": \"ok\"}",
+            record.to_json()
+        );
+        assert_eq!("6\t2\t#\"\\n #\t0.250\t1.500\tok", record.to_tsv());
+    }
+
+    #[test]
+    fn error_record() {
+        let record = record(Err("Day 6: arithmetic overflow".into()));
+        assert!(record.to_json().contains("\"answer\": null, \"error\": \"Day 6: arithmetic overflow\""));
+        assert!(record.to_json().ends_with("\"status\": \"error\"}"));
+        assert_eq!("6\t2\t\t0.250\t1.500\terror", record.to_tsv());
+    }
+}
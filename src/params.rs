@@ -0,0 +1,137 @@
+//! Tunable constants of the puzzles, such as the number of simulated steps.
+//!
+//! Each day declares its parameters with a default and the range of values
+//! it supports. The runner resolves `--param key=value` into `Values` that
+//! are handed to the day along with its input. Empty values give the
+//! defaults, which the recorded answers and examples always use.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+#[derive(Debug, PartialEq)]
+pub struct Param {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub default: usize,
+    pub range: RangeInclusive<usize>,
+}
+
+/// Values overriding the defaults of a day's parameters
+pub type Values = BTreeMap<&'static str, usize>;
+
+impl Param {
+    /// The value in `values`, the default otherwise
+    pub fn get(self: &Self, values: &Values) -> usize {
+        values.get(self.name).copied().unwrap_or(self.default)
+    }
+}
+
+impl fmt::Display for Param {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = format!("{}={}", self.name, self.default);
+        write!(f, "{:<18} {} ({}..={})", name, self.description, self.range.start(), self.range.end())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParamError {
+    Malformed(String),
+    Unknown(String),
+    InvalidValue(String, String),
+    OutOfRange(&'static Param, usize),
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParamError::Malformed(s) => write!(f, "Expected a parameter key=value, got '{}'", s),
+            ParamError::Unknown(key) => write!(f, "Unknown parameter '{}'", key),
+            ParamError::InvalidValue(key, value) => write!(f, "Invalid value '{}' for parameter '{}'", value, key),
+            ParamError::OutOfRange(param, value) => write!(
+                f, "Parameter '{}' must be in {}..={}, got {}",
+                param.name, param.range.start(), param.range.end(), value
+            ),
+        }
+    }
+}
+
+/// Split a `key=value` argument
+pub fn split(arg: &str) -> Result<(String, String), ParamError> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => Ok((key.trim().into(), value.trim().into())),
+        _ => Err(ParamError::Malformed(arg.into())),
+    }
+}
+
+/// The values of the `overrides` that belong to `params`. Keys the day does
+/// not declare are left out, they may belong to another selected day.
+pub fn resolve(params: &'static [Param], overrides: &[(String, String)]) -> Result<Values, ParamError> {
+    let mut values = Values::new();
+    for (key, value) in overrides {
+        let param = match params.iter().find(|p| p.name == key) {
+            Some(param) => param,
+            None => continue,
+        };
+        let v: usize = value.parse().map_err(|_| ParamError::InvalidValue(key.clone(), value.clone()))?;
+        if !param.range.contains(&v) {
+            return Err(ParamError::OutOfRange(param, v));
+        }
+        values.insert(param.name, v);
+    }
+    Ok(values)
+}
+
+/// Fail for the first override none of the `params` declares
+pub fn check_known<'a, I>(params: I, overrides: &[(String, String)]) -> Result<(), ParamError>
+    where I: IntoIterator<Item=&'a Param> + Clone
+{
+    match overrides.iter().find(|(key, _)| !params.clone().into_iter().any(|p| p.name == key)) {
+        Some((key, _)) => Err(ParamError::Unknown(key.clone())),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const STEPS: Param = Param{ name: "steps", description: "steps to simulate", default: 100, range: 1..=1000 };
+    static PARAMS: &[Param] = &[STEPS];
+
+    fn overrides(args: &[&str]) -> Vec<(String, String)> {
+        args.iter().map(|a| split(a).unwrap()).collect()
+    }
+
+    #[test]
+    fn split_param() {
+        assert_eq!(Ok(("steps".into(), "5".into())), split("steps=5"));
+        assert_eq!(Err(ParamError::Malformed("steps".into())), split("steps"));
+        assert_eq!(Err(ParamError::Malformed("=5".into())), split("=5"));
+    }
+
+    #[test]
+    fn resolve_values() {
+        let values = resolve(PARAMS, &overrides(&["steps=5", "basins=2"])).unwrap();
+        assert_eq!(Some(&5), values.get("steps"));
+        assert_eq!(1, values.len());
+        assert_eq!(
+            Err(ParamError::InvalidValue("steps".into(), "x".into())),
+            resolve(PARAMS, &overrides(&["steps=x"]))
+        );
+        assert_eq!(Err(ParamError::OutOfRange(&PARAMS[0], 0)), resolve(PARAMS, &overrides(&["steps=0"])));
+        assert_eq!(Err(ParamError::Unknown("basins".into())), check_known(PARAMS, &overrides(&["steps=1", "basins=2"])));
+    }
+
+    #[test]
+    fn override_default() {
+        assert_eq!(100, STEPS.get(&Values::new()));
+        let values = resolve(PARAMS, &overrides(&["steps=5"])).unwrap();
+        assert_eq!(5, STEPS.get(&values));
+    }
+
+    #[test]
+    fn describe_param() {
+        assert_eq!("steps=100          steps to simulate (1..=1000)", STEPS.to_string());
+    }
+}
@@ -0,0 +1,333 @@
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::str::FromStr;
+
+/// A parse failure, located in the puzzle input.
+/// `text` is the offending line and `column` the (0 based) character in it
+/// where parsing failed.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParseError {
+    pub day: Option<u8>,
+    pub line: Option<usize>, // 1 based
+    pub column: usize,
+    pub text: String,
+    pub expected: String,
+}
+
+impl ParseError {
+    pub fn new<E: Into<String>>(text: &str, expected: E) -> Self {
+        ParseError {
+            day: None,
+            line: None,
+            column: 0,
+            text: text.to_string(),
+            expected: expected.into(),
+        }
+    }
+
+    /// Error in `text` at the start of `part`, which must be a slice of `text`
+    pub fn at<E: Into<String>>(text: &str, part: &str, expected: E) -> Self {
+        Self::new(part, expected).within(text, part)
+    }
+
+    /// Re-locate an error reported for `part` into the enclosing `text`
+    pub fn within(mut self: Self, text: &str, part: &str) -> Self {
+        self.column += char_offset(text, part);
+        self.text = text.to_string();
+        self
+    }
+
+    pub fn line(mut self: Self, line: usize) -> Self {
+        self.line = Some(line);
+        self
+    }
+
+    pub fn day(mut self: Self, day: u8) -> Self {
+        self.day = Some(day);
+        self
+    }
+}
+
+/// Offset in characters of `part` within `text`, 0 if `part` is not a slice of `text`
+fn char_offset(text: &str, part: &str) -> usize {
+    let start = text.as_ptr() as usize;
+    let pos = part.as_ptr() as usize;
+    if pos < start || pos > start + text.len() { return 0; }
+    text[..pos - start].chars().count()
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Parse error")?;
+        if let Some(day) = self.day { write!(f, " in day {}", day)?; }
+        if let Some(line) = self.line { write!(f, " at line {}", line)?; }
+        writeln!(f, ", column {}: expected {}", self.column + 1, self.expected)?;
+        let gutter = self.line.map(|l| l.to_string()).unwrap_or_default();
+        writeln!(f, "{} | {}", gutter, self.text)?;
+        write!(f, "{} | {}^", " ".repeat(gutter.len()), " ".repeat(self.column))
+    }
+}
+
+/// How the line based parsers handle malformed lines
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Mode {
+    #[default]
+    Strict,     // Reject the input at the first malformed line
+    Lenient,    // Skip malformed lines and items, they are kept by the `Context`
+}
+
+/// The mode an input is parsed in, handed to every parser of the input,
+/// and the errors of the lines it skipped so far. The default is strict.
+#[derive(Debug, Default)]
+pub struct Context {
+    mode: Mode,
+    skipped: RefCell<Vec<ParseError>>,
+}
+
+impl Context {
+    pub fn new(mode: Mode) -> Self {
+        Context{ mode, skipped: RefCell::new(Vec::new()) }
+    }
+
+    /// Record a malformed line or item when parsing leniently, otherwise
+    /// fail with it
+    pub fn skip(self: &Self, error: ParseError) -> Result<(), ParseError> {
+        match self.mode {
+            Mode::Strict => Err(error),
+            Mode::Lenient => { self.skipped.borrow_mut().push(error); Ok(()) }
+        }
+    }
+
+    /// The errors of all skipped lines and items, always empty in strict mode
+    pub fn into_skipped(self: Self) -> Vec<ParseError> {
+        self.skipped.into_inner()
+    }
+
+    /// Parse all non-empty lines with `f`. Lines are trimmed before parsing,
+    /// errors are reported with their line number counted from `first_line`.
+    /// Malformed lines are skipped in lenient mode.
+    pub fn lines_from<T, F>(self: &Self, s: &str, first_line: usize, f: F) -> Result<Vec<T>, ParseError>
+        where F: Fn(&str) -> Result<T, ParseError>
+    {
+        self.numbered_lines_from(s, first_line, |_, line| f(line))
+    }
+
+    pub fn lines<T, F>(self: &Self, s: &str, f: F) -> Result<Vec<T>, ParseError>
+        where F: Fn(&str) -> Result<T, ParseError>
+    {
+        self.lines_from(s, 1, f)
+    }
+
+    /// Like `lines`, but `f` is also given the number of the line it parses
+    pub fn numbered_lines<T, F>(self: &Self, s: &str, f: F) -> Result<Vec<T>, ParseError>
+        where F: Fn(usize, &str) -> Result<T, ParseError>
+    {
+        self.numbered_lines_from(s, 1, f)
+    }
+
+    fn numbered_lines_from<T, F>(self: &Self, s: &str, first_line: usize, f: F) -> Result<Vec<T>, ParseError>
+        where F: Fn(usize, &str) -> Result<T, ParseError>
+    {
+        let mut parsed = Vec::new();
+        for (i, line) in s.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            let trimmed = line.trim();
+            match f(first_line + i, trimmed) {
+                Ok(value) => parsed.push(value),
+                Err(e) => self.skip(e.within(line, trimmed).line(first_line + i))?,
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Parse the comma separated items of all non-empty lines with `f`, in
+    /// order. Items are trimmed before parsing. Only the malformed items are
+    /// skipped in lenient mode, not their whole line.
+    pub fn items_from<T, F>(self: &Self, s: &str, first_line: usize, f: F) -> Result<Vec<T>, ParseError>
+        where F: Fn(&str) -> Result<T, ParseError>
+    {
+        let mut parsed = Vec::new();
+        for (i, line) in s.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            for item in line.split(',').map(str::trim) {
+                match f(item) {
+                    Ok(value) => parsed.push(value),
+                    Err(e) => self.skip(e.within(line, item).line(first_line + i))?,
+                }
+            }
+        }
+        Ok(parsed)
+    }
+
+    pub fn items<T, F>(self: &Self, s: &str, f: F) -> Result<Vec<T>, ParseError>
+        where F: Fn(&str) -> Result<T, ParseError>
+    {
+        self.items_from(s, 1, f)
+    }
+}
+
+/// Parse a single token with `FromStr`, reporting failures in `text`
+pub fn token<T: FromStr>(text: &str, token: &str, expected: &str) -> Result<T, ParseError> {
+    token.parse::<T>().map_err(|_| ParseError::at(text, token, expected))
+}
+
+/// Parse a rectangular block of single digits, one row per line
+pub fn digit_rows(s: &str, context: &Context) -> Result<Vec<Vec<u8>>, ParseError> {
+    let width = Cell::new(None);
+    let rows = context.lines(s, |line| {
+        let row = line
+            .char_indices()
+            .map(|(i, c)| {
+                c.to_digit(10)
+                    .map(|d| d as u8)
+                    .ok_or_else(|| ParseError::at(line, &line[i..], "a digit"))
+            })
+            .collect::<Result<Vec<u8>, ParseError>>()?;
+        match width.get() {
+            None => width.set(Some(row.len())),
+            Some(w) if w != row.len() => {
+                let at = line.char_indices().nth(w).map(|(i, _)| &line[i..]).unwrap_or(&line[line.len()..]);
+                return Err(ParseError::at(line, at, format!("{} digits per row", w)));
+            }
+            _ => {}
+        }
+        Ok(row)
+    })?;
+    if rows.is_empty() {
+        return Err(ParseError::new(s.trim(), "at least one row of digits"));
+    }
+    Ok(rows)
+}
+
+/// A block of the input separated from the others by empty lines
+#[derive(Debug, PartialEq)]
+pub struct Section<'a> {
+    pub first_line: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Section<'a> {
+    pub fn lines<T, F>(self: &Self, context: &Context, f: F) -> Result<Vec<T>, ParseError>
+        where F: Fn(&str) -> Result<T, ParseError>
+    {
+        context.lines_from(self.text, self.first_line, f)
+    }
+
+    pub fn items<T, F>(self: &Self, context: &Context, f: F) -> Result<Vec<T>, ParseError>
+        where F: Fn(&str) -> Result<T, ParseError>
+    {
+        context.items_from(self.text, self.first_line, f)
+    }
+}
+
+/// Split the input at empty lines
+pub fn sections(s: &str) -> Vec<Section<'_>> {
+    let mut sections = Vec::new();
+    let mut start: Option<(usize, usize)> = None; // (line, byte offset)
+    let mut offset = 0;
+    for (i, line) in s.split('\n').enumerate() {
+        if line.trim().is_empty() {
+            if let Some((first_line, begin)) = start.take() {
+                sections.push(Section{ first_line, text: s[begin..offset].trim_end() });
+            }
+        }
+        else if start.is_none() {
+            start = Some((i + 1, offset));
+        }
+        offset += line.len() + 1;
+    }
+    if let Some((first_line, begin)) = start {
+        sections.push(Section{ first_line, text: s[begin..].trim_end() });
+    }
+    sections
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn locate_token() {
+        let line = "1,2 -> 3,x";
+        let err = token::<u64>(line, &line[9..], "a number").unwrap_err();
+        assert_eq!(9, err.column);
+        assert_eq!(line, err.text);
+    }
+
+    #[test]
+    fn relocate_error() {
+        let line = "  abc";
+        let err = ParseError::at("abc", &"abc"[1..], "a digit").within(line, &line[2..]);
+        assert_eq!(3, err.column);
+        assert_eq!(line, err.text);
+    }
+
+    #[test]
+    fn parse_lines_with_numbers() {
+        let parse = |s: &str| token::<u32>(s, s, "a number");
+        let strict = Context::default();
+        assert_eq!(Ok(vec![1, 2, 3]), strict.lines("1\n  2\n\n3\n", parse));
+        let err = strict.lines("1\n\n  x2\n", parse).unwrap_err();
+        assert_eq!(Some(3), err.line);
+        assert_eq!(2, err.column);
+        assert_eq!("  x2", err.text);
+    }
+
+    #[test]
+    fn parse_lines_leniently() {
+        let parse = |s: &str| token::<u32>(s, s, "a number");
+        let lenient = Context::new(Mode::Lenient);
+        assert_eq!(Ok(vec![1, 3]), lenient.lines("1\nx\n3\n4y\n", parse));
+        let skipped = lenient.into_skipped();
+        assert_eq!(vec![Some(2), Some(4)], skipped.iter().map(|e| e.line).collect::<Vec<_>>());
+        let strict = Context::new(Mode::Strict);
+        assert_eq!(Some(2), strict.lines("1\nx\n3\n", parse).unwrap_err().line);
+        assert!(strict.into_skipped().is_empty());
+    }
+
+    #[test]
+    fn parse_items() {
+        let parse = |s: &str| token::<u32>(s, s, "a number");
+        assert_eq!(Ok(vec![1, 2, 3]), Context::default().items("1, 2\n\n3\n", parse));
+        let err = Context::default().items("1,2\n 3,x,4\n", parse).unwrap_err();
+        assert_eq!((Some(2), 3, " 3,x,4"), (err.line, err.column, err.text.as_str()));
+
+        // Only the malformed items are skipped
+        let lenient = Context::new(Mode::Lenient);
+        assert_eq!(Ok(vec![7, 4, 5]), lenient.items_from("7,x,4\n5,,y\n", 3, parse));
+        let skipped: Vec<_> = lenient.into_skipped().iter().map(|e| (e.line, e.column)).collect();
+        assert_eq!(vec![(Some(3), 2), (Some(4), 2), (Some(4), 3)], skipped);
+    }
+
+    #[test]
+    fn parse_digit_rows() {
+        let strict = Context::default();
+        assert_eq!(Ok(vec![vec![1, 2], vec![3, 4]]), digit_rows("\n  12\n  34\n", &strict));
+        let err = digit_rows("12\n1x", &strict).unwrap_err();
+        assert_eq!((Some(2), 1), (err.line, err.column));
+        let err = digit_rows("12\n123", &strict).unwrap_err();
+        assert_eq!((Some(2), 2, "2 digits per row"), (err.line, err.column, err.expected.as_str()));
+        let err = digit_rows("123\n12", &strict).unwrap_err();
+        assert_eq!((Some(2), 2), (err.line, err.column));
+        assert!(digit_rows("  \n", &strict).is_err());
+    }
+
+    #[test]
+    fn split_sections() {
+        let s = "a\nb\n\nc\n\n\nd\ne\n";
+        let sections = sections(s);
+        assert_eq!(vec![
+            Section{ first_line: 1, text: "a\nb" },
+            Section{ first_line: 4, text: "c" },
+            Section{ first_line: 7, text: "d\ne" },
+        ], sections);
+        assert!(super::sections("\n\n").is_empty());
+    }
+
+    #[test]
+    fn display_snippet() {
+        let err = ParseError::at("8,0 -> 0,", &"8,0 -> 0,"[7..], "x,y").line(3).day(5);
+        assert_eq!(
+            "Parse error in day 5 at line 3, column 8: expected x,y\n3 | 8,0 -> 0,\n  |        ^",
+            err.to_string()
+        );
+    }
+}
@@ -1,30 +1,208 @@
-const INPUT: &str = include_str!("../data/1/input");
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
 
-fn parse_input(s: &str) -> Vec<i32>
+use crate::params::Values;
+use crate::parse;
+use crate::solution::{Answer, Context, ParseError, Solution, SolveError};
+
+pub fn parse_depth(line: &str) -> Result<i32, ParseError>
+{
+    parse::token(line, line, "a depth")
+}
+
+pub fn parse_input(s: &str, context: &Context) -> Result<Vec<i32>, ParseError>
+{
+    context.lines(s, parse_depth)
+}
+
+/// What a `DepthAnalyzer` found out about the windows of a sonar sweep
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct DepthStats {
+    pub increases: usize,
+    pub decreases: usize,
+    pub plateaus: usize,
+    pub longest_increasing_run: usize,  // In consecutive increases
+    pub increase_indices: Vec<usize>,
+}
+
+/// Compares the sum of each window of `window` consecutive depths with the
+/// previous window. Depths are fed one at a time and only the last window is
+/// kept: the sums differ by the depth entering and the one leaving, so they
+/// are never computed and cannot overflow.
+///
+/// An increase is reported at the index of the depth whose arrival made the
+/// window deeper, e.g. 1 for the first two depths of `[199, 200]`.
+#[derive(Debug, Clone)]
+pub struct DepthAnalyzer
+{
+    window: usize,
+    recent: VecDeque<i32>,
+    seen: usize,
+    run: usize,
+    record_indices: bool,
+    stats: DepthStats,
+}
+
+impl DepthAnalyzer
 {
-    s
-        .split('\n') // Lines
-        .filter_map(|item| item.parse::<i32>().ok()) // parse to i32 (if some)
-        .collect() // collect the i32
+    /// Panics if `window` is 0
+    pub fn new(window: usize) -> Self
+    {
+        assert!(window > 0, "The depth window must not be empty");
+        DepthAnalyzer {
+            window,
+            recent: VecDeque::with_capacity(window + 1),
+            seen: 0,
+            run: 0,
+            record_indices: true,
+            stats: DepthStats::default(),
+        }
+    }
+
+    /// Only count, leaving `increase_indices` empty: for logs with too many
+    /// increases to keep their indices
+    pub fn without_indices(mut self: Self) -> Self
+    {
+        self.record_indices = false;
+        self
+    }
+
+    pub fn push(self: &mut Self, depth: i32)
+    {
+        self.recent.push_back(depth);
+        if self.recent.len() > self.window {
+            let leaving = self.recent.pop_front().unwrap();
+            match depth.cmp(&leaving) {
+                Ordering::Greater => {
+                    self.stats.increases += 1;
+                    self.run += 1;
+                    self.stats.longest_increasing_run = self.stats.longest_increasing_run.max(self.run);
+                    if self.record_indices {
+                        self.stats.increase_indices.push(self.seen);
+                    }
+                }
+                Ordering::Less => {
+                    self.stats.decreases += 1;
+                    self.run = 0;
+                }
+                Ordering::Equal => {
+                    self.stats.plateaus += 1;
+                    self.run = 0;
+                }
+            }
+        }
+        self.seen += 1;
+    }
+
+    pub fn stats(self: &Self) -> &DepthStats
+    {
+        &self.stats
+    }
+
+    pub fn finish(self: Self) -> DepthStats
+    {
+        self.stats
+    }
+
+    pub fn analyze<I: IntoIterator<Item=i32>>(mut self: Self, depths: I) -> DepthStats
+    {
+        for depth in depths {
+            self.push(depth);
+        }
+        self.finish()
+    }
+
+    /// Analyze the depths of `reader`, one per line, holding a single line in
+    /// memory. Malformed depths fail with `InvalidData`.
+    pub fn read<R: BufRead>(mut self: Self, mut reader: R) -> io::Result<DepthStats>
+    {
+        let mut buffer = String::new();
+        let mut line = 0;
+        while reader.read_line(&mut buffer)? > 0 {
+            line += 1;
+            let text = buffer.trim_end_matches(['\n', '\r']);
+            let depth = text.trim();
+            if !depth.is_empty() {
+                let depth = parse_depth(depth).map_err(|e| io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    e.within(text, depth).line(line).day(1).to_string(),
+                ))?;
+                self.push(depth);
+            }
+            buffer.clear();
+        }
+        Ok(self.finish())
+    }
 }
 
-pub fn count_increasing_depth() -> usize
+pub fn count_increasing_depth(data: &[i32]) -> usize
 {
-    let data = parse_input(INPUT);
-    let it1 = data.iter();
-    let mut it2 = data.iter();
-    it2.next();
-    it1.zip(it2).filter(|(i1, i2)| i2 > i1).count()
+    DepthAnalyzer::new(1).without_indices().analyze(data.iter().copied()).increases
 }
 
-pub fn count_sliding_window_increasing_depth() -> usize
+pub fn count_sliding_window_increasing_depth(data: &[i32]) -> usize
 {
-    let data = parse_input(INPUT);
-    let it1 = data.windows(3);
-    let mut it2 = it1.clone();
-    it2.next();
-    it1
-        .zip(it2)
-        .filter(|(i1, i2)| &i2.iter().sum::<i32>() > &i1.iter().sum::<i32>())
-        .count()
+    DepthAnalyzer::new(3).without_indices().analyze(data.iter().copied()).increases
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Parsed = Vec<i32>;
+
+    fn parse(input: &str, _params: &Values, context: &Context) -> Result<Self::Parsed, ParseError> {
+        parse_input(input, context)
+    }
+
+    fn part1(depths: &Self::Parsed, _params: &Values) -> Result<Answer, SolveError> {
+        Ok(count_increasing_depth(depths).into())
+    }
+
+    fn part2(depths: &Self::Parsed, _params: &Values) -> Result<Answer, SolveError> {
+        Ok(count_sliding_window_increasing_depth(depths).into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: [i32; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+
+    #[test]
+    fn puzzle_windows()
+    {
+        assert_eq!(7, count_increasing_depth(&EXAMPLE));
+        assert_eq!(5, count_sliding_window_increasing_depth(&EXAMPLE));
+    }
+
+    #[test]
+    fn analyze_depths()
+    {
+        let stats = DepthAnalyzer::new(1).analyze(EXAMPLE);
+        assert_eq!((7, 2, 0), (stats.increases, stats.decreases, stats.plateaus));
+        assert_eq!(3, stats.longest_increasing_run);
+        assert_eq!(vec![1, 2, 3, 5, 6, 7, 9], stats.increase_indices);
+
+        // Window sums 607 618 618 617 647 716 769 792
+        let stats = DepthAnalyzer::new(3).analyze(EXAMPLE);
+        assert_eq!((5, 1, 1), (stats.increases, stats.decreases, stats.plateaus));
+        assert_eq!(4, stats.longest_increasing_run);
+        assert_eq!(vec![3, 6, 7, 8, 9], stats.increase_indices);
+
+        let stats = DepthAnalyzer::new(10).analyze(EXAMPLE);
+        assert_eq!(DepthStats::default(), stats);
+        assert!(DepthAnalyzer::new(1).without_indices().analyze(EXAMPLE).increase_indices.is_empty());
+    }
+
+    #[test]
+    fn read_depths()
+    {
+        let input: String = EXAMPLE.iter().map(|d| format!("{}\r\n", d)).collect();
+        assert_eq!(DepthAnalyzer::new(3).analyze(EXAMPLE), DepthAnalyzer::new(3).read(input.as_bytes()).unwrap());
+        let err = DepthAnalyzer::new(1).read("1\n\n 2x\n".as_bytes()).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert!(err.to_string().starts_with("Parse error in day 1 at line 3, column 2: expected a depth"));
+    }
 }
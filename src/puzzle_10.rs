@@ -1,12 +1,13 @@
 use std::str::FromStr;
 
-const INPUT: &str = include_str!("../data/10/input");
+use crate::params::Values;
+use crate::solution::{Answer, Context, ParseError, Solution, SolveError};
 
 #[derive(Debug, Clone, PartialEq)]
-enum Scope { OPEN, CLOSE}
+pub enum Scope { OPEN, CLOSE}
 
 #[derive(Debug, Clone, PartialEq)]
-enum Class {
+pub enum Class {
     PAREN,
     SQUARE,
     CURLY,
@@ -14,7 +15,7 @@ enum Class {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-struct Token ( Class, Scope, );
+pub struct Token ( Class, Scope, );
 
 #[derive(Debug, Clone, PartialEq)]
 struct SyntaxError(Class);
@@ -37,20 +38,17 @@ impl Token {
     }
 }
 
-struct NavLine( Vec::<Token> );
+pub struct NavLine( Vec::<Token> );
 
 
 impl FromStr for NavLine {
-    type Err = &'static str;
+    type Err = ParseError;
     fn from_str(s: &str) -> Result::<Self, Self::Err> {
-        let tokens = s.chars().map(Token::from_char).collect::<Vec::<Result::<Token, &'static str>>>();
-        if tokens.iter().all(|t| t.is_ok()) {
-            let tokens = tokens.into_iter().map(|t| t.unwrap()).collect::<Vec::<Token>>();
-            Ok(Self(tokens))
-        }
-        else {
-            Err("Invalid token")
-        }
+        let tokens = s
+            .char_indices()
+            .map(|(i, c)| Token::from_char(c).map_err(|_| ParseError::at(s, &s[i..], "one of ()[]{}<>")))
+            .collect::<Result::<Vec::<Token>, ParseError>>()?;
+        Ok(Self(tokens))
     }
 }
 
@@ -130,19 +128,15 @@ fn score_completion(completion: &Class) -> u64 {
     }
 }
 
-fn score_completions(completion: &Vec::<Class>) -> u64 {
+fn score_completions(completion: &[Class]) -> u64 {
     completion.iter().fold(0u64, |acc, comp| acc*5 + score_completion(comp))
 }
 
-fn parse_input(s: &str) -> Vec::<NavLine> {
-    s.trim().split('\n')
-        .map(str::trim)
-        .map(NavLine::from_str)
-        .map(|l| l.expect("Invalid input"))
-        .collect()
+fn parse_input(s: &str, context: &Context) -> Result<Vec::<NavLine>, ParseError> {
+    context.lines(s, NavLine::from_str)
 }
 
-fn calculate_error_score(data: &Vec::<NavLine>) -> u64 {
+fn calculate_error_score(data: &[NavLine]) -> u64 {
     data
         .iter()
         .filter_map(NavLine::error)
@@ -150,7 +144,7 @@ fn calculate_error_score(data: &Vec::<NavLine>) -> u64 {
         .sum()
 }
 
-fn calculate_middle_complete_score(data: &Vec::<NavLine>) -> u64 {
+fn calculate_middle_complete_score(data: &[NavLine]) -> u64 {
     let mut completion_scores: Vec<u64> = data
         .iter()
         .filter_map(NavLine::complete)
@@ -160,12 +154,22 @@ fn calculate_middle_complete_score(data: &Vec::<NavLine>) -> u64 {
     completion_scores[completion_scores.len()/2]
 }
 
-pub fn syntax_error_score() -> u64 {
-    calculate_error_score(&parse_input(INPUT))
-}
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Parsed = Vec<NavLine>;
+
+    fn parse(input: &str, _params: &Values, context: &Context) -> Result<Self::Parsed, ParseError> {
+        parse_input(input, context)
+    }
+
+    fn part1(lines: &Self::Parsed, _params: &Values) -> Result<Answer, SolveError> {
+        Ok(calculate_error_score(lines).into())
+    }
 
-pub fn completion_score() -> u64 {
-    calculate_middle_complete_score(&parse_input(INPUT))
+    fn part2(lines: &Self::Parsed, _params: &Values) -> Result<Answer, SolveError> {
+        Ok(calculate_middle_complete_score(lines).into())
+    }
 }
 
 #[cfg(test)]
@@ -174,18 +178,7 @@ mod test {
     use Class::*;
     use Scope::*;
 
-    const EXAMPLE: &str = "
-        [({(<(())[]>[[{[]{<()<>>
-        [(()[<>])]({[<{<<[]>>(
-        {([(<{}[<>[]}>{[]{[(<()>
-        (((({<>}<{<{<>}{[]{[]{}
-        [[<[([]))<([[{}[[()]]]
-        [{[{({}]{}}([{[{{{}}([]
-        {<[[]]>}<{[{[{[]{()[[[]
-        [<(<(<(<{}))><([]([]()
-        <{([([[(<>()){}]>(<<{{
-        <{([{{}}[<[[[<>{}]]]>[]]
-    ";
+    const EXAMPLE: &str = include_str!("../data/10/examples/example.in");
 
     #[test]
     fn parse_token() {
@@ -212,12 +205,12 @@ mod test {
         assert_eq!(15, line.0.len());
 
         let line = "))*[]}".parse::<NavLine>();
-        assert!(line.is_err());
+        assert_eq!(2, line.err().unwrap().column);
     }
 
     #[test]
     fn parse_example_input() {
-        let navline = parse_input(EXAMPLE);
+        let navline = parse_input(EXAMPLE, &Context::default()).unwrap();
         assert_eq!(10, navline.len());
         assert_eq!(Token(ANGLE, OPEN), navline[4].0[2]);
     }
@@ -233,7 +226,7 @@ mod test {
 
     #[test]
     fn error_scoring() {
-        let errors = vec![
+        let errors = [
             SyntaxError(PAREN),  // 3
             SyntaxError(SQUARE), // 57
             SyntaxError(CURLY),  // 1197
@@ -245,13 +238,15 @@ mod test {
 
     #[test]
     fn example_error_score() {
-        let input = parse_input(EXAMPLE);
+        let input = parse_input(EXAMPLE, &Context::default()).unwrap();
         assert_eq!(26397u64, calculate_error_score(&input));
     }
 
+    const INPUT: &str = include_str!("../data/10/input");
+
     #[test]
     fn solution_error_score() {
-        let input = parse_input(INPUT);
+        let input = parse_input(INPUT, &Context::default()).unwrap();
         assert_eq!(316851u64, calculate_error_score(&input));
     }
 
@@ -278,7 +273,7 @@ mod test {
 
     #[test]
     fn example_complete_score() {
-        let input = parse_input(EXAMPLE);
+        let input = parse_input(EXAMPLE, &Context::default()).unwrap();
         assert_eq!(288957u64, calculate_middle_complete_score(&input));
     }
     
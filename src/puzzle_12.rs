@@ -3,13 +3,14 @@ use std::collections::HashSet;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
-const INPUT: &str = include_str!("../data/12/input");
+use crate::params::Values;
+use crate::solution::{Answer, Context, ParseError, Solution, SolveError};
 
-type ID = u64;
+pub type ID = u64;
 type Set<T> = HashSet<T>;
 
 #[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone)]
-enum Cave {
+pub enum Cave {
     Start,
     End,
     Small(ID),
@@ -17,28 +18,30 @@ enum Cave {
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
-struct Tunnel { 
+pub struct Tunnel { 
     from: Cave,
     to: Cave
 }
 
 #[derive(Debug)]
-struct CaveMap {
+pub struct CaveMap {
     tunnels: Vec<Tunnel>,
 }
 
 #[derive(Debug, Clone)]
-struct Path ( Vec<Cave> );
+pub struct Path ( Vec<Cave> );
+
+const CAVE_NAME: &str = "a cave name (start, end, all upper or all lower case)";
 
 impl FromStr for Cave{
-    type Err = ();
+    type Err = ParseError;
     fn from_str(id: &str) -> Result<Self, Self::Err> {
-        if id.is_empty() { Err(()) }
+        if id.is_empty() { Err(ParseError::new(id, CAVE_NAME)) }
         else if id == "start" { Ok(Cave::Start) }
         else if id == "end" { Ok(Cave::End) }
         else if id.chars().all(|c| c.is_uppercase()) { Ok(Cave::Large(hash(id))) }
         else if id.chars().all(|c| c.is_lowercase()) { Ok(Cave::Small(hash(id))) }
-        else { Err(()) }
+        else { Err(ParseError::new(id, CAVE_NAME)) }
     }
 }
 
@@ -49,16 +52,17 @@ fn hash(s: &str) -> u64 {
 }
 
 impl FromStr for Tunnel {
-    type Err = ();
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut caves = s
-            .trim()
             .split('-')
             .map(str::trim)
-            .map(Cave::from_str);
-        let from = caves.next().ok_or(())??;
-        let to   = caves.next().ok_or(())??;
-        if caves.next().is_some() { return Err(()) } // More than 2 caves
+            .map(|c| c.parse::<Cave>().map_err(|e| e.within(s, c)));
+        let from = caves.next().unwrap_or_else(|| Err(ParseError::new(s, CAVE_NAME)))?;
+        let to   = caves.next().unwrap_or_else(|| Err(ParseError::at(s, &s[s.len()..], "a tunnel cave-cave")))?;
+        if let Some(i) = s.match_indices('-').nth(1).map(|(i, _)| i) { // More than 2 caves
+            return Err(ParseError::at(s, &s[i..], "end of line"));
+        }
         Ok(Tunnel{from, to})
     }
 }
@@ -72,6 +76,13 @@ impl FromIterator<Tunnel> for CaveMap {
     }
 }
 
+impl FromStr for CaveMap {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_input(s, &Context::default())
+    }
+}
+
 impl CaveMap {
 
     fn caves<'a, T: Iterator<Item=&'a Tunnel>>(tunnels: T) -> Set<Cave> {
@@ -93,7 +104,7 @@ impl CaveMap {
         caves
     }
 
-    fn count_paths(self: &Self, validator: fn(&Path) -> bool) -> u64 {
+    pub fn count_paths(self: &Self, validator: fn(&Path) -> bool) -> u64 {
         let mut paths = vec![Path(vec![Cave::Start])];
         let mut count = 0u64;
         loop {
@@ -102,8 +113,7 @@ impl CaveMap {
                 .filter(|p| !p.complete())
                 .flat_map(|p| {
                     let connected = self.connected(p.tip());
-                    let n = extend(p, connected);
-                    n
+                    extend(p, connected)
                 })
                 .filter(validator)
                 .collect();
@@ -115,18 +125,18 @@ impl CaveMap {
 }
 
 impl Path {
-    fn complete(self: &Self) -> bool {
+    pub fn complete(self: &Self) -> bool {
         self.0.first() == Some(&Cave::Start) && 
         self.0.last() == Some(&Cave::End)
     }
 
-    fn single_visit_validator(p: &Path) -> bool {
+    pub fn single_visit_validator(p: &Path) -> bool {
         p._double_visits() == 0 &&
         !p._double_stay() &&
         !p._multi_start()
     }
 
-    fn double_visit_validator(p: &Path) -> bool {
+    pub fn double_visit_validator(p: &Path) -> bool {
         p._double_visits() < 2 &&
         !p._double_stay() &&
         !p._multi_start()
@@ -137,7 +147,7 @@ impl Path {
             .iter()
             .filter_map(
                 |c| match c {
-                    Cave::Small(id) => Some(id.clone()),
+                    Cave::Small(id) => Some(*id),
                     _ => None
                 }
             )
@@ -156,7 +166,7 @@ impl Path {
         self.0.iter().filter(|c| **c == Cave::Start).count() > 1
     }
 
-    fn tip(self: &Self) -> &Cave {
+    pub fn tip(self: &Self) -> &Cave {
         self.0.last().expect("non empty path")
     }
 }
@@ -172,21 +182,26 @@ fn extend(p: Path, c: Set<Cave>) -> Vec<Path> {
 }
 
 
-fn parse_input(s: &str) -> CaveMap {
-    s
-        .trim()
-        .split('\n')
-        .map(str::trim)
-        .map(|t| t.parse::<Tunnel>().expect("Valid tunnel spec"))
-        .collect()
+fn parse_input(s: &str, context: &Context) -> Result<CaveMap, ParseError> {
+    Ok(context.lines(s, Tunnel::from_str)?.into_iter().collect())
 }
 
-pub fn number_of_paths() -> u64 {
-    parse_input(INPUT).count_paths(Path::single_visit_validator)
-}
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Parsed = CaveMap;
 
-pub fn number_of_paths_double_visit() -> u64 {
-    parse_input(INPUT).count_paths(Path::double_visit_validator)
+    fn parse(input: &str, _params: &Values, context: &Context) -> Result<Self::Parsed, ParseError> {
+        parse_input(input, context)
+    }
+
+    fn part1(map: &Self::Parsed, _params: &Values) -> Result<Answer, SolveError> {
+        Ok(map.count_paths(Path::single_visit_validator).into())
+    }
+
+    fn part2(map: &Self::Parsed, _params: &Values) -> Result<Answer, SolveError> {
+        Ok(map.count_paths(Path::double_visit_validator).into())
+    }
 }
 
 
@@ -195,9 +210,9 @@ mod test {
     
     use super::*;
 
-    const EX1: &str = include_str!("../data/12/example1");
-    const EX2: &str = include_str!("../data/12/example2");
-    const EX3: &str = include_str!("../data/12/example3");
+    const EX1: &str = include_str!("../data/12/examples/example1.in");
+    const EX2: &str = include_str!("../data/12/examples/example2.in");
+    const EX3: &str = include_str!("../data/12/examples/example3.in");
 
     #[test]
     fn parse_cave() {
@@ -222,22 +237,23 @@ mod test {
         assert!("a--".parse::<Tunnel>().is_err());
         assert!("a--".parse::<Tunnel>().is_err());
         assert!("a-b-c".parse::<Tunnel>().is_err());
+        assert_eq!(3, "a-b-c".parse::<Tunnel>().unwrap_err().column);
+        assert_eq!(2, "a-Bc".parse::<Tunnel>().unwrap_err().column);
     }
 
     #[test]
     fn cave_map_from_iter() {
-        let mut paths = Vec::<Tunnel>::new();
-        paths.push(Tunnel{
+        let paths = vec![Tunnel{
             from: Cave::Small(hash("a")),
             to: Cave::Small(hash("b")),
-        });
+        }];
         let map: CaveMap = paths.into_iter().collect();
-        assert_eq!(2, CaveMap::caves(map.tunnels.iter()).iter().count());
+        assert_eq!(2, CaveMap::caves(map.tunnels.iter()).len());
     }
 
     #[test]
     fn connected_caves() {
-        let map = parse_input(EX1);
+        let map = parse_input(EX1, &Context::default()).unwrap();
         assert_eq!(2, map.connected(&Cave::Start).len());
         assert_eq!(4, map.connected(&Cave::Large(hash("A"))).len());
         assert_eq!(1, map.connected(&Cave::Small(hash("d"))).len());
@@ -245,15 +261,15 @@ mod test {
 
     #[test]
     fn path_is_complete() {
-        assert_eq!(false, Path(vec![]).complete());
-        assert_eq!(false, Path(vec![Cave::Start]).complete());
-        assert_eq!(true, Path(vec![Cave::Start, Cave::End]).complete());
-        assert_eq!(true, Path(vec![Cave::Start, Cave::Small(0), Cave::End]).complete());
+        assert!(!Path(vec![]).complete());
+        assert!(!Path(vec![Cave::Start]).complete());
+        assert!(Path(vec![Cave::Start, Cave::End]).complete());
+        assert!(Path(vec![Cave::Start, Cave::Small(0), Cave::End]).complete());
     }
 
     #[test]
     fn path_is_valid() {
-        let valid_paths = vec![
+        let valid_paths = [
             Path(vec![]),
             Path(vec![Cave::Start]),
             Path(vec![Cave::Start, Cave::End]),
@@ -271,7 +287,7 @@ mod test {
 
     #[test]
     fn path_invalid_mutliple_visits_small() {
-        assert_eq!(false, Path::single_visit_validator(
+        assert!(!Path::single_visit_validator(
             &Path(vec![
                 Cave::Start,
                 Cave::Small(0),
@@ -283,7 +299,7 @@ mod test {
 
     #[test]
     fn path_invalid_stay_in_one_cave() {
-        assert_eq!(false, Path::single_visit_validator(
+        assert!(!Path::single_visit_validator(
             &Path(vec![
                 Cave::Start,
                 Cave::Large(0),
@@ -295,7 +311,7 @@ mod test {
 
     #[test]
     fn path_invalid_multi_start() {
-        assert_eq!(false, Path::single_visit_validator(
+        assert!(!Path::single_visit_validator(
             &Path(vec![
                 Cave::Start,
                 Cave::Small(0),
@@ -316,27 +332,29 @@ mod test {
 
     #[test]
     fn example_1() {
-        assert_eq!(10, parse_input(EX1).count_paths(Path::single_visit_validator));
-        assert_eq!(36, parse_input(EX1).count_paths(Path::double_visit_validator));
+        assert_eq!(10, parse_input(EX1, &Context::default()).unwrap().count_paths(Path::single_visit_validator));
+        assert_eq!(36, parse_input(EX1, &Context::default()).unwrap().count_paths(Path::double_visit_validator));
     }
 
     #[test]
     fn example_2() {
-        assert_eq!(19, parse_input(EX2).count_paths(Path::single_visit_validator));
-        assert_eq!(103, parse_input(EX2).count_paths(Path::double_visit_validator));
+        assert_eq!(19, parse_input(EX2, &Context::default()).unwrap().count_paths(Path::single_visit_validator));
+        assert_eq!(103, parse_input(EX2, &Context::default()).unwrap().count_paths(Path::double_visit_validator));
     }
 
     #[test]
     fn example_3() {
-        assert_eq!(226, parse_input(EX3).count_paths(Path::single_visit_validator));
-        assert_eq!(3509, parse_input(EX3).count_paths(Path::double_visit_validator));
+        assert_eq!(226, parse_input(EX3, &Context::default()).unwrap().count_paths(Path::single_visit_validator));
+        assert_eq!(3509, parse_input(EX3, &Context::default()).unwrap().count_paths(Path::double_visit_validator));
     }
 
+    const INPUT: &str = include_str!("../data/12/input");
+
     #[allow(unused)]
     //#[test] // Takes too long to run all the time :(
     fn solution() {
-        assert_eq!(4495, parse_input(INPUT).count_paths(Path::single_visit_validator));
-        assert_eq!(131254, parse_input(INPUT).count_paths(Path::double_visit_validator));
+        assert_eq!(4495, parse_input(INPUT, &Context::default()).unwrap().count_paths(Path::single_visit_validator));
+        assert_eq!(131254, parse_input(INPUT, &Context::default()).unwrap().count_paths(Path::double_visit_validator));
     }
 
 }
@@ -1,52 +1,35 @@
 use std::str::FromStr;
 use std::collections::BTreeSet;
+use std::fmt;
 
-const DOTS: &str = include_str!("../data/13/dots");
-const FOLDS: &str = include_str!("../data/13/folds");
+use crate::grid::Grid;
+use crate::params::Values;
+use crate::parse::{self, Section};
+use crate::solution::{Answer, Context, ParseError, Solution, SolveError};
+use crate::vec2::{self, Vec2};
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
-struct Dot(i32, i32);
+pub type Dot = Vec2<i32>;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
-enum Fold{ X(i32), Y(i32) }
+pub enum Fold{ X(i32), Y(i32) }
 
-#[derive(Debug)]
-struct Dots(BTreeSet<Dot>);
-
-impl FromStr for Dot {
-    type Err = ();
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s
-            .trim()
-            .split(',')
-            .map(str::trim)
-            .map(i32::from_str);
-
-        match (parts.next(), parts.next(), parts.next()) {
-            (Some(Ok(x)), Some(Ok(y)), None) => {Ok(Dot(x, y))},
-            _ => {Err(())}
-        }
-    }
-}
+#[derive(Debug, Clone)]
+pub struct Dots(BTreeSet<Dot>);
 
 impl FromStr for Fold {
-    type Err = ();
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s
-            .trim()
-            .split('=')
-            .map(str::trim);
-
-        match (parts.next(), parts.next(), parts.next()) {
-            (Some("fold along x"), Some(x), None) => {
-                let x = x.parse::<i32>().map_err(|_| ())?;
-                Ok(Fold::X(x))
-            },
-            (Some("fold along y"), Some(y), None) => {
-                let y = y.parse::<i32>().map_err(|_| ())?;
-                Ok(Fold::Y(y))
-            },
-            _ => {Err(())}
+        let fold = s
+            .strip_prefix("fold along ")
+            .ok_or_else(|| ParseError::new(s, "fold along x=.. or y=.."))?;
+        let (axis, position) = fold
+            .split_once('=')
+            .ok_or_else(|| ParseError::at(s, fold, "fold along x=.. or y=.."))?;
+        let position = parse::token(s, position.trim(), "a fold position")?;
+        match axis.trim() {
+            "x" => Ok(Fold::X(position)),
+            "y" => Ok(Fold::Y(position)),
+            _ => Err(ParseError::at(s, axis, "x or y")),
         }
     }
 }
@@ -74,69 +57,76 @@ impl Dots {
     }
 }
 
-impl ToString for Dots {
-    fn to_string(self: &Self) -> String {
-        let dots = &self.0;
-        let max_x = dots.iter().map(|d| d.0).max().unwrap() as usize;
-        let max_y = dots.iter().map(|d| d.1).max().unwrap() as usize;
-        let mut display = String::with_capacity((max_x+1)*max_y);
-        for y in 0..=max_y {
-            for x in 0..=max_x {
-                if dots.contains(&Dot(x as i32,y as i32)) {
-                    display.push('#');
-                }
-                else {
-                    display.push(' ');
-                }
-            }
-            display.push('\n');
+impl Dots {
+    /// The sheet up to the furthest dot with a `true` cell for each dot. It
+    /// starts at the origin, or further up and left for dots folded to
+    /// negative coordinates.
+    pub fn to_grid(self: &Self) -> Grid<bool> {
+        let (min, max) = vec2::bounding_box(self.0.iter().copied())
+            .map_or((Dot::ZERO, Dot::new(-1, -1)), |(min, max)| (min.min(Dot::ZERO), max));
+        let size = max - min + Dot::new(1, 1);
+        let mut grid = Grid::filled(size.y as usize, size.x as usize, false);
+        for &dot in &self.0 {
+            grid[(dot - min).map(|v| v as isize)] = true;
         }
-        display
+        grid
+    }
+}
+
+impl fmt::Display for Dots {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_grid().map(|&dot| if dot { '#' } else { ' ' }))
     }
 }
 
-fn parse_dots(s: &str) -> Dots
+fn parse_dots(section: &Section, context: &Context) -> Result<Dots, ParseError>
 {
-    s
-        .trim()
-        .lines()
-        .map(str::trim)
-        .map(Dot::from_str)
-        .map(Result::unwrap)
-        .collect()
+    Ok(section.lines(context, Dot::from_str)?.into_iter().collect())
 }
 
-fn parse_folds(s: &str) -> Vec<Fold>
+fn parse_folds(section: &Section, context: &Context) -> Result<Vec<Fold>, ParseError>
 {
-    s
-        .trim()
-        .lines()
-        .map(str::trim)
-        .map(Fold::from_str)
-        .map(Result::unwrap)
-        .collect()
+    section.lines(context, Fold::from_str)
+}
+
+/// The input is the list of dots followed by the fold instructions, separated by an empty line
+fn parse_input(s: &str, context: &Context) -> Result<(Dots, Vec<Fold>), ParseError>
+{
+    match parse::sections(s).as_slice() {
+        [] => Err(ParseError::new("", "a list of dots x,y")),
+        [dots] => Ok((parse_dots(dots, context)?, Vec::new())),
+        [dots, folds] => Ok((parse_dots(dots, context)?, parse_folds(folds, context)?)),
+        [_, _, extra, ..] => {
+            let line = extra.text.lines().next().unwrap_or_default();
+            Err(ParseError::new(line, "end of input").line(extra.first_line))
+        }
+    }
 }
 
 fn fold_dot(dot: Dot, fold: Fold) -> Dot {
     match fold {
-        Fold::X(x) if dot.0 > x => { Dot(2*x - dot.0, dot.1) }
-        Fold::Y(y) if dot.1 > y => { Dot(dot.0, 2*y - dot.1) }
+        Fold::X(x) if dot.x > x => { Dot::new(2*x - dot.x, dot.y) }
+        Fold::Y(y) if dot.y > y => { Dot::new(dot.x, 2*y - dot.y) }
         _ => { dot }
     }
 }
 
-pub fn dots_after_one_fold() -> u64 {
-    let mut dots: Dots = parse_dots(DOTS);
-    let folds: Vec<Fold> = parse_folds(FOLDS);
-    dots = dots.fold(folds.into_iter().take(1));
-    dots.len() as u64
-}
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Parsed = (Dots, Vec<Fold>);
+
+    fn parse(input: &str, _params: &Values, context: &Context) -> Result<Self::Parsed, ParseError> {
+        parse_input(input, context)
+    }
 
-pub fn fold_and_format_dots() -> String {
-    let dots: Dots = parse_dots(DOTS);
-    let folds: Vec<Fold> = parse_folds(FOLDS);
-    let dots = dots.fold(folds.into_iter());
-    dots.to_string()
+    fn part1((dots, folds): &Self::Parsed, _params: &Values) -> Result<Answer, SolveError> {
+        Ok(dots.clone().fold(folds.iter().copied().take(1)).len().into())
+    }
+
+    fn part2((dots, folds): &Self::Parsed, _params: &Values) -> Result<Answer, SolveError> {
+        Ok(dots.clone().fold(folds.iter().copied()).to_string().into())
+    }
 }
 
 #[cfg(test)]
@@ -146,8 +136,8 @@ mod test {
 
     #[test]
     fn dot_parsing() {
-        assert_eq!(Dot(0, 0), "0,0".parse::<Dot>().unwrap());
-        assert_eq!(Dot(3, -4), " 3, -4".parse::<Dot>().unwrap());
+        assert_eq!(Dot::new(0, 0), "0,0".parse::<Dot>().unwrap());
+        assert_eq!(Dot::new(3, -4), " 3, -4".parse::<Dot>().unwrap());
         assert!("".parse::<Dot>().is_err());
         assert!("1,".parse::<Dot>().is_err());
         assert!("1,2,3".parse::<Dot>().is_err());
@@ -165,41 +155,58 @@ mod test {
 
     #[test]
     fn fold_x() {
-        let dots    = [Dot(5,7), Dot(20, 1), Dot(0,0), Dot(4, 4)];
+        let dots    = [Dot::new(5, 7), Dot::new(20, 1), Dot::new(0, 0), Dot::new(4, 4)];
         let fold    = Fold::X(4);
-        let expect  = [Dot(3,7), Dot(-12, 1), Dot(0,0), Dot(4, 4)];
-        for (dot, exp) in dots.into_iter().zip(expect.into_iter()) {
+        let expect  = [Dot::new(3, 7), Dot::new(-12, 1), Dot::new(0, 0), Dot::new(4, 4)];
+        for (dot, exp) in dots.into_iter().zip(expect) {
             assert_eq!(exp, fold_dot(dot, fold));
         }
     }
 
     #[test]
     fn fold_y() {
-        let dots    = [Dot(7,5), Dot(1, 20), Dot(0,0), Dot(4, 4)];
+        let dots    = [Dot::new(7, 5), Dot::new(1, 20), Dot::new(0, 0), Dot::new(4, 4)];
         let fold    = Fold::Y(4);
-        let expect  = [Dot(7,3), Dot(1, -12), Dot(0,0), Dot(4, 4)];
-        for (dot, exp) in dots.into_iter().zip(expect.into_iter()) {
+        let expect  = [Dot::new(7, 3), Dot::new(1, -12), Dot::new(0, 0), Dot::new(4, 4)];
+        for (dot, exp) in dots.into_iter().zip(expect) {
             assert_eq!(exp, fold_dot(dot, fold));
         }
     }
 
     #[test]
     fn dot_collection() {
-        let dots = [Dot(7,5), Dot(1, 20), Dot(0,0), Dot(4, 4)];
+        let dots = [Dot::new(7, 5), Dot::new(1, 20), Dot::new(0, 0), Dot::new(4, 4)];
         let coll: Dots = dots.into_iter().collect();
         assert_eq!(4, coll.0.len());
     }
 
-    const EX_DOTS: &str = include_str!("../data/13/example_dots");
-    const EX_FOLDS: &str = include_str!("../data/13/example_folds");
+    const EXAMPLE: &str = include_str!("../data/13/examples/example.in");
 
     #[test]
     fn single_fold_example() {
-        let dots: Dots = parse_dots(EX_DOTS);
-        let folds: Vec<Fold> = parse_folds(EX_FOLDS);
+        let (dots, folds) = parse_input(EXAMPLE, &Context::default()).unwrap();
         let dots = dots.fold(folds.into_iter().take(1));
         assert_eq!(17, dots.len());
 
     }
 
+    #[test]
+    fn negative_dots_grid() {
+        let (dots, folds) = parse_input("0,0\n10,0\n\nfold along x=2\n", &Context::default()).unwrap();
+        // 10,0 folds to -6,0
+        assert_eq!("#     #\n", dots.fold(folds).to_string());
+        let dots: Dots = [Dot::new(-1, -2), Dot::new(-3, -1)].into_iter().collect();
+        assert_eq!("  #\n#  \n", dots.to_string());
+        assert_eq!((0, 0), Dots(BTreeSet::new()).to_grid().shape());
+    }
+
+    #[test]
+    fn input_error_location() {
+        let err = parse_input("6,10\n0,14\n\nfold along y=7\nfold along z=5\n", &Context::default()).unwrap_err();
+        assert_eq!((Some(5), 11, "x or y"), (err.line, err.column, err.expected.as_str()));
+        let err = parse_input("6,10\n0,x\n", &Context::default()).unwrap_err();
+        assert_eq!((Some(2), 2), (err.line, err.column));
+        assert!(parse_input("6,10\n\nfold along y=7\n\n1,1\n", &Context::default()).is_err());
+    }
+
 }
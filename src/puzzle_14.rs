@@ -2,27 +2,29 @@ use std::str::FromStr;
 use std::collections::HashMap;
 use std::ops::Index;
 
-const POLYMER: &str = include_str!("../data/14/polymer");
-const RULES: &str = include_str!("../data/14/rules");
+use crate::parse;
+use crate::params::{Param, Values};
+use crate::solution::{Answer, Context, ParseError, Solution, SolveError};
 
-type Pair = (char, char);
+pub type Pair = (char, char);
 
 #[derive(Debug, PartialEq)]
-struct Rule {
+pub struct Rule {
     input: Pair,
     output: (Pair, Pair),
 }
 
-struct Rules ( HashMap<Pair, (Pair, Pair)> );
+#[derive(Debug)]
+pub struct Rules ( HashMap<Pair, (Pair, Pair)> );
 
-#[derive(Debug, Default)]
-struct Polymer{ 
+#[derive(Debug, Default, Clone)]
+pub struct Polymer{ 
     initial: String,
     pair_counts: HashMap<Pair, usize>,
 }
 
 impl Rule {
-    fn new(input: (char, char), insert: char) -> Self {
+    pub fn new(input: (char, char), insert: char) -> Self {
         Rule{
             input,
             output: ((input.0, insert), (insert, input.1))
@@ -31,29 +33,19 @@ impl Rule {
 }
 
 impl FromStr for Rule {
-    type Err = ();
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Rule, Self::Err> {
-        let mut parts = s
-            .trim()
-            .split("->")
-            .map(str::trim);
-
-        match (parts.next(), parts.next(), parts.next()) {
-            (Some(pair), Some(insert), None) => {
-                let mut p = pair.chars();
-                let input = match (p.next(), p.next(), p.next()) {
-                    (Some(a), Some(b), None) =>  { Ok( (a, b) ) }
-                    _ => { Err(()) }
-                };
-                let mut i = insert.chars();
-                let insert = match (i.next(), i.next()) {
-                    (Some(i),  None) =>  { Ok( i ) }
-                    _ => { Err(()) }
-                };
-                Ok(Rule::new(input?, insert?))
-            },
-            _ => { Err(()) } 
-        }
+        let (pair, insert) = s.split_once("->").ok_or_else(|| ParseError::new(s, "a rule AB -> C"))?;
+        let (pair, insert) = (pair.trim(), insert.trim());
+        let input = match pair.chars().collect::<Vec<char>>()[..] {
+            [a, b] => (a, b),
+            _ => return Err(ParseError::at(s, pair, "a pair of elements")),
+        };
+        let insert = match insert.chars().collect::<Vec<char>>()[..] {
+            [i] => i,
+            _ => return Err(ParseError::at(s, insert, "a single element")),
+        };
+        Ok(Rule::new(input, insert))
     }
 }
 
@@ -67,14 +59,8 @@ impl FromIterator<Rule> for Rules {
     }
 }
 
-fn parse_rules(s: &str) -> Rules {
-    s
-        .trim()
-        .lines()
-        .map(str::trim)
-        .map(Rule::from_str)
-        .map(Result::unwrap)
-        .collect()
+pub fn parse_rules(s: &str) -> Result<Rules, ParseError> {
+    Ok(Context::default().lines(s, Rule::from_str)?.into_iter().collect())
 }
 
 struct Pairs<'a> ( std::iter::Peekable<std::str::Chars<'a>> );
@@ -91,15 +77,9 @@ impl<'a> Iterator for Pairs<'a> {
 
 impl Polymer {
 
-    fn new(s: &str) -> Self {
+    pub fn new(s: &str) -> Self {
         let mut pair_counts = HashMap::<Pair, usize>::new();
-        pairs(s)
-            .for_each(|p|
-                match pair_counts.get_mut(&p) {
-                    Some(c) => { *c += 1; },
-                    None => { pair_counts.insert(p, 1); }
-                }
-            );
+        pairs(s).for_each(|p| *pair_counts.entry(p).or_insert(0) += 1);
         Self{pair_counts, initial: s.into()}
     }
 
@@ -112,10 +92,9 @@ impl Polymer {
         self.pair_counts.insert(k, new_val as usize);
     }
 
-    fn polymerize(self: &mut Self, rules: &Rules, steps: usize) {
+    pub fn polymerize(self: &mut Self, rules: &Rules, steps: usize) {
         for _ in 0..steps {
-            let mut next = Polymer::default();
-            next.initial = self.initial.clone();
+            let mut next = Polymer { initial: self.initial.clone(), ..Default::default() };
             for (p0, v) in self.pair_counts.iter() {
                match rules.0.get(p0) {
                    Some((p1, p2)) => {
@@ -139,7 +118,7 @@ impl Polymer {
         self.initial.chars().last().expect("Non empty initial polymer")
     }
 
-    fn counts(self: &Self) -> ElementCounts {
+    pub fn counts(self: &Self) -> ElementCounts {
         let mut counts = ElementCounts::new();
         for (k, v) in self.pair_counts.iter() {
             counts.update(k.0, *v as isize);
@@ -160,18 +139,18 @@ impl<T> From<T> for Polymer
     }
 }
 
-fn pairs<'a> (s: &'a str) -> Pairs<'a> {
+fn pairs(s: &str) -> Pairs<'_> {
     Pairs(s.chars().peekable())
 }
 
 #[derive(Debug)]
-struct ElementCounts(HashMap<char, usize>);
+pub struct ElementCounts(HashMap<char, usize>);
 
 impl Index<char> for ElementCounts {
     type Output = usize;
     fn index(self: &Self, c: char) -> &Self::Output {
         match self.0.get(&c) {
-            Some(count) => &count,
+            Some(count) => count,
             None => &0
         }
     }
@@ -189,22 +168,77 @@ impl ElementCounts{
         if new_val < 0 { new_val = 0; }
         self.0.insert(k, new_val as usize);
     }
-    fn max(self: &Self) -> usize {
-        *self.0.iter().map(|(_, v)| v).max().unwrap_or(&0)
+    pub fn max(self: &Self) -> usize {
+        *self.0.values().max().unwrap_or(&0)
+    }
+    pub fn min(self: &Self) -> usize {
+        *self.0.values().min().unwrap_or(&0)
     }
-    fn min(self: &Self) -> usize {
-        *self.0.iter().map(|(_, v)| v).min().unwrap_or(&0)
+}
+
+/// The input is the template polymer followed by the insertion rules, separated by an empty line
+fn parse_input(s: &str, context: &Context) -> Result<(Polymer, Rules), ParseError> {
+    let sections = parse::sections(s);
+    let (template, rules) = match sections.as_slice() {
+        [template, rules] => (template, rules),
+        [template] => return Err(
+            ParseError::new("", "an empty line followed by the insertion rules").line(template.first_line + 1)
+        ),
+        _ => return Err(ParseError::new(s.trim(), "a polymer template followed by the insertion rules")),
+    };
+    let polymer = template.lines(context, |line| {
+        match line.find(|c: char| !c.is_ascii_uppercase()) {
+            Some(i) => Err(ParseError::at(line, &line[i..], "an element A-Z")),
+            None => Ok(Polymer::from(line)),
+        }
+    })?;
+    if polymer.len() > 1 {
+        let line = template.text.lines().nth(1).unwrap_or_default();
+        return Err(ParseError::new(line, "an empty line after the template").line(template.first_line + 1));
     }
+    // Lenient parsing may have skipped the template
+    let polymer = polymer
+        .into_iter()
+        .next()
+        .ok_or_else(|| ParseError::new(template.text, "a polymer template").line(template.first_line))?;
+    Ok((polymer, Rules::from_iter(rules.lines(context, Rule::from_str)?)))
 }
 
-pub fn polymer_index(steps: usize) -> u64 {
-    let mut polymer = Polymer::from(POLYMER.trim());
-    let rules = parse_rules(RULES);
-    polymer.polymerize(&rules, steps);
+pub const PART1_STEPS: Param = Param{
+    name: "part1_steps", description: "insertion steps of part 1", default: 10, range: 0..=50,
+};
+
+pub const PART2_STEPS: Param = Param{
+    name: "part2_steps", description: "insertion steps of part 2", default: 40, range: 0..=50,
+};
+
+pub const PARAMS: &[Param] = &[PART1_STEPS, PART2_STEPS];
+
+fn polymer_index(polymer: &Polymer, rules: &Rules, steps: usize) -> u64 {
+    let mut polymer = polymer.clone();
+    polymer.polymerize(rules, steps);
     let counts = polymer.counts();
     (counts.max()-counts.min()) as u64
 }
 
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Parsed = (Polymer, Rules);
+
+    fn parse(input: &str, _params: &Values, context: &Context) -> Result<Self::Parsed, ParseError> {
+        parse_input(input, context)
+    }
+
+    fn part1((polymer, rules): &Self::Parsed, params: &Values) -> Result<Answer, SolveError> {
+        Ok(polymer_index(polymer, rules, PART1_STEPS.get(params)).into())
+    }
+
+    fn part2((polymer, rules): &Self::Parsed, params: &Values) -> Result<Answer, SolveError> {
+        Ok(polymer_index(polymer, rules, PART2_STEPS.get(params)).into())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -218,6 +252,23 @@ mod test {
         assert!("ABC -> D".parse::<Rule>().is_err());
         assert!("AB -> CD".parse::<Rule>().is_err());
         assert!("AB -> C -> D".parse::<Rule>().is_err());
+        assert_eq!(6, "AB -> CD".parse::<Rule>().unwrap_err().column);
+    }
+
+    #[test]
+    fn test_parse_input_errors() {
+        assert!(parse_input("NNCB\n\nCH -> B\n", &Context::default()).is_ok());
+        let err = parse_input("NNcB\n\nCH -> B\n", &Context::default()).unwrap_err();
+        assert_eq!((Some(1), 2), (err.line, err.column));
+        let err = parse_input("NNCB\n\nCH -> B\nHH - N\n", &Context::default()).unwrap_err();
+        assert_eq!((Some(4), 0, "a rule AB -> C"), (err.line, err.column, err.expected.as_str()));
+        assert_eq!(Some(2), parse_input("NNCB\nCH -> B\n", &Context::default()).unwrap_err().line);
+        assert!(parse_input("NNCB\n", &Context::default()).is_err());
+
+        let lenient = Context::new(parse::Mode::Lenient);
+        let err = parse_input("nncb\n\nCH -> B", &lenient).unwrap_err();
+        assert_eq!((Some(1), "a polymer template"), (err.line, err.expected.as_str()));
+        assert_eq!(1, lenient.into_skipped().len());
     }
     
     #[test]
@@ -266,12 +317,11 @@ mod test {
         assert_eq!(0, counts['D']);
     }
 
-    const EX_RULES: &str = include_str!("../data/14/example_rules");
+    const EXAMPLE: &str = include_str!("../data/14/examples/example.in");
 
     #[test]
     fn test_example() {
-        let mut polymer = Polymer::from("NNCB");
-        let rules = parse_rules(EX_RULES);
+        let (mut polymer, rules) = parse_input(EXAMPLE, &Context::default()).unwrap();
         polymer.polymerize(&rules, 10);
         let counts = polymer.counts();
         assert_eq!(1588, counts.max()-counts.min());
@@ -1,119 +1,950 @@
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
 use std::str::FromStr;
 
-const INPUT: &str = include_str!("../data/2/input");
+use crate::params::Values;
+use crate::parse;
+use crate::solution::{Answer, Context, ParseError, Solution, SolveError};
 
-#[derive(Debug)]
-enum Direction 
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Direction 
 {
     UP, 
     DOWN, 
     FORWARD,
+    BACK,
+    SURFACE,    // Straight up to depth 0, takes no step size
 }
 
-#[derive(Debug)]
-struct Move 
+#[derive(Debug, PartialEq, Clone)]
+pub struct Move 
 {
-    direction: Direction,
-    stepsize: i32,
+    pub direction: Direction,
+    pub stepsize: i32,
 }
 
-#[derive(Debug)]
-struct Position
+/// An arithmetic operation overflowed the numeric type of a `Position`
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Overflow;
+
+impl fmt::Display for Overflow {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.write_str("arithmetic overflow")
+    }
+}
+
+/// The numbers a `Position` can be made of. Their arithmetic is always
+/// checked, an operation that does not fit gives an `Overflow`. `Checked`
+/// offers the same as operators.
+pub trait Number: Copy + Default + PartialOrd + fmt::Debug + fmt::Display + From<i32>
+{
+    fn checked_add(self: Self, other: Self) -> Result<Self, Overflow>;
+    fn checked_sub(self: Self, other: Self) -> Result<Self, Overflow>;
+    fn checked_mul(self: Self, other: Self) -> Result<Self, Overflow>;
+    fn to_i128(self: Self) -> i128;
+}
+
+macro_rules! number {
+    ($($t:ty),*) => {
+        $(
+            impl Number for $t {
+                fn checked_add(self: Self, other: Self) -> Result<Self, Overflow> { <$t>::checked_add(self, other).ok_or(Overflow) }
+                fn checked_sub(self: Self, other: Self) -> Result<Self, Overflow> { <$t>::checked_sub(self, other).ok_or(Overflow) }
+                fn checked_mul(self: Self, other: Self) -> Result<Self, Overflow> { <$t>::checked_mul(self, other).ok_or(Overflow) }
+                fn to_i128(self: Self) -> i128 { self as i128 }
+            }
+        )*
+    }
+}
+
+number!(i32, i64, i128);
+
+/// A number whose `+`, `-` and `*` give an `Overflow` error instead of
+/// wrapping, to be unwrapped with `?`. It is a `Number` itself, so a
+/// `Position<Checked<i64>>` can be handed to code written with operators.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Default)]
+pub struct Checked<N>(pub N);
+
+impl<N: Number> Add for Checked<N> {
+    type Output = Result<Self, Overflow>;
+    fn add(self: Self, other: Self) -> Self::Output
+    {
+        self.0.checked_add(other.0).map(Checked)
+    }
+}
+
+impl<N: Number> Sub for Checked<N> {
+    type Output = Result<Self, Overflow>;
+    fn sub(self: Self, other: Self) -> Self::Output
+    {
+        self.0.checked_sub(other.0).map(Checked)
+    }
+}
+
+impl<N: Number> Mul for Checked<N> {
+    type Output = Result<Self, Overflow>;
+    fn mul(self: Self, other: Self) -> Self::Output
+    {
+        self.0.checked_mul(other.0).map(Checked)
+    }
+}
+
+impl<N: Number> From<i32> for Checked<N> {
+    fn from(v: i32) -> Self
+    {
+        Checked(N::from(v))
+    }
+}
+
+impl<N: fmt::Display> fmt::Display for Checked<N> {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        self.0.fmt(f)
+    }
+}
+
+impl<N: Number> Number for Checked<N>
+{
+    fn checked_add(self: Self, other: Self) -> Result<Self, Overflow> { self + other }
+    fn checked_sub(self: Self, other: Self) -> Result<Self, Overflow> { self - other }
+    fn checked_mul(self: Self, other: Self) -> Result<Self, Overflow> { self * other }
+    fn to_i128(self: Self) -> i128 { self.0.to_i128() }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Position<N: Number = i64>
 {
-    horizontal: i32,
-    depth: i32,
-    aim: i32,
+    pub horizontal: N,
+    pub depth: N,
+    pub aim: N,
 }
 
-impl Position {
-    fn new() -> Self
+impl<N: Number> Position<N> {
+    pub fn new() -> Self
+    {
+        Position::default()
+    }
+
+    pub fn metric(self: &Self) -> Result<N, Overflow>
     {
-        Position{horizontal: 0, depth: 0, aim: 0}
+        self.horizontal.checked_mul(self.depth)
     }
+}
+
+/// What a move does to the position of the submarine. Models may keep state
+/// of their own between moves.
+pub trait MovementModel
+{
+    fn apply<N: Number>(self: &mut Self, position: &mut Position<N>, m: &Move) -> Result<(), Overflow>;
+}
 
-    fn update(self: &mut Self, m: Move) -> () 
+/// Up and down change the depth directly (part 1)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Simple;
+
+impl MovementModel for Simple
+{
+    fn apply<N: Number>(self: &mut Self, position: &mut Position<N>, m: &Move) -> Result<(), Overflow>
     {
         use Direction::*;
+        let step = N::from(m.stepsize);
         match m.direction {
-            UP => {self.depth -= m.stepsize;}
-            DOWN => {self.depth += m.stepsize;}
-            FORWARD => {self.horizontal += m.stepsize;}
+            UP => {position.depth = position.depth.checked_sub(step)?;}
+            DOWN => {position.depth = position.depth.checked_add(step)?;}
+            FORWARD => {position.horizontal = position.horizontal.checked_add(step)?;}
+            BACK => {position.horizontal = position.horizontal.checked_sub(step)?;}
+            SURFACE => {position.depth = N::default();}
         }
+        Ok(())
     }
+}
 
-    fn update_aimed(self: &mut Self, m: Move) -> ()
+/// Up and down change the aim, which moving forward follows (part 2).
+/// Moving back retraces the aim, surfacing also levels the aim.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Aimed;
+
+impl MovementModel for Aimed
+{
+    fn apply<N: Number>(self: &mut Self, position: &mut Position<N>, m: &Move) -> Result<(), Overflow>
     {
         use Direction::*;
+        let step = N::from(m.stepsize);
         match m.direction {
             UP => {
-                self.aim -= m.stepsize;
+                position.aim = position.aim.checked_sub(step)?;
             }
             DOWN => {
-                self.aim += m.stepsize;
+                position.aim = position.aim.checked_add(step)?;
             }
             FORWARD => {
-                self.horizontal += m.stepsize;
-                self.depth += self.aim*m.stepsize;
+                position.horizontal = position.horizontal.checked_add(step)?;
+                position.depth = position.depth.checked_add(position.aim.checked_mul(step)?)?;
+            }
+            BACK => {
+                position.horizontal = position.horizontal.checked_sub(step)?;
+                position.depth = position.depth.checked_sub(position.aim.checked_mul(step)?)?;
+            }
+            SURFACE => {
+                position.depth = N::default();
+                position.aim = N::default();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A move that overflowed the position, steps counted from 1
+#[derive(Debug, PartialEq, Clone)]
+pub struct OverflowError
+{
+    pub step: usize,
+    pub command: Move,
+}
+
+impl fmt::Display for OverflowError {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "Overflow at step {} ({})", self.step, self.command)
+    }
+}
+
+/// Runs moves under a movement model, keeping its position in `N`
+#[derive(Debug, Clone)]
+pub struct Submarine<M: MovementModel, N: Number = i64>
+{
+    model: M,
+    position: Position<N>,
+}
+
+impl<M: MovementModel> Submarine<M>
+{
+    /// At the surface, in `i64`
+    pub fn new(model: M) -> Self
+    {
+        Submarine::starting_at(model, Position::new())
+    }
+}
+
+impl<M: MovementModel, N: Number> Submarine<M, N>
+{
+    pub fn starting_at(model: M, position: Position<N>) -> Self
+    {
+        Submarine{model, position}
+    }
+
+    pub fn position(self: &Self) -> Position<N>
+    {
+        self.position
+    }
+
+    /// A move that overflows leaves the position as it was
+    pub fn execute(self: &mut Self, m: &Move) -> Result<(), Overflow>
+    {
+        let mut position = self.position;
+        self.model.apply(&mut position, m)?;
+        self.position = position;
+        Ok(())
+    }
+
+    /// Execute all `moves`, returning the final position
+    pub fn run<'a, I: IntoIterator<Item=&'a Move>>(self: &mut Self, moves: I) -> Result<Position<N>, OverflowError>
+    {
+        for (i, m) in moves.into_iter().enumerate() {
+            self.execute(m).map_err(|_| OverflowError{step: i + 1, command: m.clone()})?;
+        }
+        Ok(self.position)
+    }
+
+    pub fn trajectory(self: Self, moves: &[Move]) -> Trajectory<'_, M, N>
+    {
+        Trajectory{submarine: Some(self), moves: moves.iter().enumerate()}
+    }
+}
+
+/// The positions of a submarine after each move: `(step, move, position)`
+/// with steps counted from 1. Ends after the first move that overflows.
+pub struct Trajectory<'a, M: MovementModel, N: Number>
+{
+    submarine: Option<Submarine<M, N>>,
+    moves: std::iter::Enumerate<std::slice::Iter<'a, Move>>,
+}
+
+impl<'a, M: MovementModel, N: Number> Iterator for Trajectory<'a, M, N>
+{
+    type Item = Result<(usize, &'a Move, Position<N>), OverflowError>;
+
+    fn next(self: &mut Self) -> Option<Self::Item>
+    {
+        let submarine = self.submarine.as_mut()?;
+        let (i, m) = self.moves.next()?;
+        match submarine.execute(m) {
+            Ok(()) => Some(Ok((i + 1, m, submarine.position()))),
+            Err(_) => {
+                self.submarine = None;
+                Some(Err(OverflowError{step: i + 1, command: m.clone()}))
             }
         }
     }
+}
+
+/// A recorded trajectory, for reviewing where the submarine went
+#[derive(Debug, PartialEq, Clone)]
+pub struct Track<N: Number = i64>
+{
+    pub start: Position<N>,
+    pub steps: Vec<(usize, Move, Position<N>)>,
+}
+
+impl<N: Number> Track<N>
+{
+    pub fn record<M: MovementModel>(submarine: Submarine<M, N>, moves: &[Move]) -> Result<Self, OverflowError>
+    {
+        let start = submarine.position();
+        let steps = submarine
+            .trajectory(moves)
+            .map(|step| step.map(|(step, m, position)| (step, m.clone(), position)))
+            .collect::<Result<_, _>>()?;
+        Ok(Track{start, steps})
+    }
+
+    /// The deepest depth and the first step reaching it, `None` without moves
+    pub fn max_depth(self: &Self) -> Option<(usize, N)>
+    {
+        self.steps
+            .iter()
+            .map(|(step, _, position)| (*step, position.depth))
+            .reduce(|deepest, p| if p.1 > deepest.1 { p } else { deepest })
+    }
+
+    /// The steps after which the submarine was above the water
+    pub fn surfacing(self: &Self) -> Vec<(usize, Position<N>)>
+    {
+        self.steps
+            .iter()
+            .filter(|(_, _, position)| position.depth < N::default())
+            .map(|(step, _, position)| (*step, *position))
+            .collect()
+    }
+
+    pub fn to_csv(self: &Self) -> String
+    {
+        let mut csv = String::from("step,move,horizontal,depth,aim\n");
+        for (step, m, p) in &self.steps {
+            csv += &format!("{},{},{},{},{}\n", step, m, p.horizontal, p.depth, p.aim);
+        }
+        csv
+    }
+
+    /// The path seen from the side in at most `width` by `height`
+    /// characters, from the start on the left with depth growing downwards.
+    /// `~` marks the surface and `#` the positions, `S` the start.
+    pub fn side_view(self: &Self, width: usize, height: usize) -> String
+    {
+        let points: Vec<(i128, i128)> = std::iter::once(self.start)
+            .chain(self.steps.iter().map(|s| s.2))
+            .map(|p| (p.horizontal.to_i128(), p.depth.to_i128()))
+            .collect();
+        let horizontal = || points.iter().map(|p| p.0).chain(std::iter::once(0));
+        let depth = || points.iter().map(|p| p.1).chain(std::iter::once(0));
+        let (left, right) = (horizontal().min().unwrap(), horizontal().max().unwrap());
+        let (top, bottom) = (depth().min().unwrap(), depth().max().unwrap());
+        // Spans of huge positions may not fit an i128 themselves
+        let span = |min: i128, max: i128| max.abs_diff(min);
+        let scale = |v: i128, min: i128, max: i128, size: usize| -> usize {
+            if max == min { return 0; }
+            (v.abs_diff(min) as f64 / span(min, max) as f64 * (size - 1) as f64).round() as usize
+        };
+        let columns = span(left, right).saturating_add(1).min(width.max(1) as u128) as usize;
+        let rows = span(top, bottom).saturating_add(1).min(height.max(1) as u128) as usize;
+        let mut view = vec![vec![' '; columns]; rows];
+        view[scale(0, top, bottom, rows)].fill('~');
+        for &(h, d) in &points[1..] {
+            view[scale(d, top, bottom, rows)][scale(h, left, right, columns)] = '#';
+        }
+        let (h, d) = points[0];
+        view[scale(d, top, bottom, rows)][scale(h, left, right, columns)] = 'S';
+        view.iter().map(|row| row.iter().collect::<String>().trim_end().to_string() + "\n").collect()
+    }
+}
 
-    fn metric(self: &Self) -> i32
+impl fmt::Display for Direction {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result
     {
-        self.horizontal * self.depth
+        let name = match self {
+            Direction::UP => "up",
+            Direction::DOWN => "down",
+            Direction::FORWARD => "forward",
+            Direction::BACK => "back",
+            Direction::SURFACE => "surface",
+        };
+        f.write_str(name)
     }
 }
 
-struct ParseDirectionError {}
+impl fmt::Display for Move {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self.direction {
+            Direction::SURFACE => write!(f, "{}", self.direction),
+            _ => write!(f, "{} {}", self.direction, self.stepsize),
+        }
+    }
+}
 
 impl FromStr for Direction {
-    type Err = ParseDirectionError;
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err>
     {
         match s {
             "up" => Ok(Direction::UP),
             "down" => Ok(Direction::DOWN),
             "forward" => Ok(Direction::FORWARD),
-            _ => Err(Self::Err{})
+            "back" => Ok(Direction::BACK),
+            "surface" => Ok(Direction::SURFACE),
+            _ => Err(ParseError::new(s, "up, down, forward, back or surface"))
+        }
+    }
+}
+
+impl FromStr for Move {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        let mut it = s.split_whitespace();
+        let name = it.next().unwrap_or(s);
+        let direction: Direction = name.parse().map_err(|e: ParseError| e.within(s, name))?;
+        let stepsize = match direction {
+            Direction::SURFACE => 0,
+            _ => {
+                let stepsize = it.next().ok_or_else(|| ParseError::at(s, &s[s.len()..], "a step size"))?;
+                parse::token(s, stepsize, "a step size")?
+            }
+        };
+        if let Some(rest) = it.next() {
+            return Err(ParseError::at(s, rest, "end of line"));
+        }
+        Ok(Move{direction, stepsize})
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Statement
+{
+    MOVE(Move),
+    REPEAT(u32, Vec<Statement>),
+}
+
+/// A mission script: one command per line, `repeat <k> { ... }` blocks
+/// and `#` comments. The puzzle input is a script without blocks.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Program
+{
+    pub statements: Vec<Statement>,
+}
+
+impl Statement
+{
+    fn moves(self: &Self) -> Box<dyn Iterator<Item=&Move> + '_>
+    {
+        match self {
+            Statement::MOVE(m) => Box::new(std::iter::once(m)),
+            Statement::REPEAT(count, body) => Box::new((0..*count).flat_map(move |_| body.iter().flat_map(Statement::moves))),
+        }
+    }
+
+    fn move_count(self: &Self) -> Option<u64>
+    {
+        match self {
+            Statement::MOVE(_) => Some(1),
+            Statement::REPEAT(count, body) => count_moves(body)?.checked_mul(*count as u64),
         }
     }
 }
 
-fn parse_move(s: &str) -> Option<Move>
+/// The number of moves of `statements` unrolled, `None` beyond `u64`
+fn count_moves(statements: &[Statement]) -> Option<u64>
+{
+    statements.iter().try_fold(0u64, |total, s| total.checked_add(s.move_count()?))
+}
+
+impl Program
+{
+    /// The moves in execution order, repeat blocks unrolled as they go
+    pub fn moves(self: &Self) -> impl Iterator<Item=&Move> + '_
+    {
+        self.statements.iter().flat_map(Statement::moves)
+    }
+
+    /// The number of moves with the repeat blocks unrolled, `None` if it
+    /// does not fit into a `u64`
+    pub fn move_count(self: &Self) -> Option<u64>
+    {
+        count_moves(&self.statements)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Kind
+{
+    WORD,
+    OPEN,
+    CLOSE,
+    NEWLINE,
+    END,
+}
+
+/// A token of a script with the line it was read from, for errors
+#[derive(Debug, Clone, Copy)]
+struct Token<'a>
+{
+    kind: Kind,
+    text: &'a str,
+    source: &'a str,
+    line: usize,
+}
+
+impl<'a> Token<'a>
+{
+    fn error(self: &Self, expected: &str) -> ParseError
+    {
+        ParseError::at(self.source, self.text, expected).line(self.line)
+    }
+}
+
+/// Words, braces and the ends of lines, comments left out
+fn tokenize(s: &str) -> Vec<Token<'_>>
+{
+    let mut tokens = Vec::new();
+    let mut end = Token{kind: Kind::END, text: s, source: s, line: 1};
+    for (i, source) in s.lines().enumerate() {
+        let line = i + 1;
+        let code = source.split('#').next().unwrap_or(source).trim_end();
+        let mut rest = code;
+        loop {
+            rest = rest.trim_start();
+            let (kind, len) = match rest.chars().next() {
+                None => break,
+                Some('{') => (Kind::OPEN, 1),
+                Some('}') => (Kind::CLOSE, 1),
+                Some(_) => (Kind::WORD, rest.find(|c: char| c.is_whitespace() || c == '{' || c == '}').unwrap_or(rest.len())),
+            };
+            tokens.push(Token{kind, text: &rest[..len], source, line});
+            rest = &rest[len..];
+        }
+        tokens.push(Token{kind: Kind::NEWLINE, text: &code[code.len()..], source, line});
+        end = Token{kind: Kind::END, text: &code[code.len()..], source, line};
+    }
+    tokens.push(end);
+    tokens
+}
+
+/// The part of `source` from the start of `first` to the end of `last`,
+/// which must both be slices of it
+fn span<'a>(source: &'a str, first: &str, last: &str) -> &'a str
+{
+    let start = first.as_ptr() as usize - source.as_ptr() as usize;
+    let end = last.as_ptr() as usize - source.as_ptr() as usize + last.len();
+    &source[start..end]
+}
+
+struct Parser<'a>
 {
-    let mut it = s.split(' ');
-    Some(
-        Move {
-           direction: it.next()?.parse().ok()?,
-           stepsize: it.next()?.parse().ok()?,
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+    context: &'a Context,
+}
+
+impl<'a> Parser<'a>
+{
+    fn peek(self: &Self) -> Token<'a>
+    {
+        self.tokens[self.pos]
+    }
+
+    fn next(self: &mut Self) -> Token<'a>
+    {
+        let token = self.tokens[self.pos];
+        if token.kind != Kind::END { self.pos += 1; }
+        token
+    }
+
+    /// Statements up to the end of the script, or the `}` matching `open`
+    fn block(self: &mut Self, open: Option<Token<'a>>) -> Result<Vec<Statement>, ParseError>
+    {
+        let mut statements = Vec::new();
+        loop {
+            let token = self.peek();
+            match token.kind {
+                Kind::NEWLINE => { self.next(); }
+                Kind::END => return match open {
+                    Some(open) => Err(open.error("a matching }")),
+                    None => Ok(statements),
+                },
+                Kind::CLOSE if open.is_some() => {
+                    self.next();
+                    return Ok(statements);
+                }
+                Kind::CLOSE | Kind::OPEN => return Err(token.error("a command")),
+                Kind::WORD if token.text == "repeat" => statements.push(self.repeat()?),
+                Kind::WORD => statements.extend(self.command()?.map(Statement::MOVE)),
+            }
         }
-    )
+    }
+
+    fn repeat(self: &mut Self) -> Result<Statement, ParseError>
+    {
+        self.next();
+        let count = self.next();
+        if count.kind != Kind::WORD {
+            return Err(count.error("a repeat count"));
+        }
+        let count_value = parse::token(count.source, count.text, "a repeat count").map_err(|e| e.line(count.line))?;
+        // The brace may go on the next line
+        while self.peek().kind == Kind::NEWLINE {
+            self.next();
+        }
+        let open = self.next();
+        if open.kind != Kind::OPEN {
+            return Err(open.error("{"));
+        }
+        Ok(Statement::REPEAT(count_value, self.block(Some(open))?))
+    }
+
+    /// The words up to the end of the line or block make up a command.
+    /// Malformed commands are skipped in lenient mode.
+    fn command(self: &mut Self) -> Result<Option<Move>, ParseError>
+    {
+        let first = self.next();
+        let mut last = first;
+        while self.peek().kind == Kind::WORD {
+            last = self.next();
+        }
+        let text = span(first.source, first.text, last.text);
+        let parsed = text.parse::<Move>().map_err(|e| e.within(first.source, text).line(first.line));
+        let parsed = match (parsed, self.peek()) {
+            (Ok(_), token) if token.kind == Kind::OPEN => Err(token.error("end of line")),
+            (parsed, _) => parsed,
+        };
+        match parsed {
+            Ok(m) => Ok(Some(m)),
+            Err(e) => {
+                self.context.skip(e)?;
+                while !matches!(self.peek().kind, Kind::NEWLINE | Kind::CLOSE | Kind::END) {
+                    self.next();
+                }
+                Ok(None)
+            }
+        }
+    }
 }
 
-fn parse_input(s: &str) -> Vec<Move>
+/// A script parsed strictly
+impl FromStr for Program {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        parse_input(s, &Context::default())
+    }
+}
+
+fn parse_input(s: &str, context: &Context) -> Result<Program, ParseError>
+{
+    let mut parser = Parser{tokens: tokenize(s), pos: 0, context};
+    Ok(Program{statements: parser.block(None)?})
+}
+
+/// The product of the final horizontal position and depth, worked out in
+/// `i128`. Fails if either overflows.
+fn final_metric<'a, M, I>(model: M, moves: I) -> Result<Answer, SolveError>
+    where M: MovementModel, I: IntoIterator<Item=&'a Move>
 {
-    s
-        .split('\n')
-        .filter_map(parse_move)
-        .collect()
+    let position = Submarine::<M, i128>::starting_at(model, Position::new())
+        .run(moves)
+        .map_err(|e| SolveError(e.to_string()))?;
+    let metric = position
+        .metric()
+        .map_err(|_| SolveError("Overflow in the metric of the final position".into()))?;
+    Ok(metric.into())
 }
 
-pub fn compute_position() -> i32
+/// The most moves a script may unroll to, nested repeats multiply quickly
+pub const MAX_MOVES: u64 = 100_000_000;
+
+/// The moves of `program`, failing if it unrolls to more than `MAX_MOVES`
+fn unrolled(program: &Program) -> Result<impl Iterator<Item=&Move> + '_, SolveError>
 {
-    let data = parse_input(INPUT);
-    let mut position = Position::new();
-    for m in data {
-        position.update(m);
+    match program.move_count() {
+        Some(count) if count <= MAX_MOVES => Ok(program.moves()),
+        _ => Err(SolveError(format!("The script unrolls to more than {} moves", MAX_MOVES))),
     }
-    position.metric()
 }
 
-pub fn compute_position_aimed() -> i32
+fn compute_position<'a, I: IntoIterator<Item=&'a Move>>(moves: I) -> Result<Answer, SolveError>
+{
+    final_metric(Simple, moves)
+}
+
+fn compute_position_aimed<'a, I: IntoIterator<Item=&'a Move>>(moves: I) -> Result<Answer, SolveError>
 {
-    let data = parse_input(INPUT);
-    let mut position = Position::new();
-    for m in data {
-        position.update_aimed(m);
+    final_metric(Aimed, moves)
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Parsed = Program;
+
+    fn parse(input: &str, _params: &Values, context: &Context) -> Result<Self::Parsed, ParseError> {
+        parse_input(input, context)
+    }
+
+    fn part1(program: &Self::Parsed, _params: &Values) -> Result<Answer, SolveError> {
+        compute_position(unrolled(program)?)
+    }
+
+    fn part2(program: &Self::Parsed, _params: &Values) -> Result<Answer, SolveError> {
+        compute_position_aimed(unrolled(program)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../data/2/examples/example.in");
+
+    fn script_moves(s: &str) -> Result<Vec<Move>, ParseError>
+    {
+        Ok(parse_input(s, &Context::default())?.moves().cloned().collect())
+    }
+
+    #[test]
+    fn movement_models()
+    {
+        let moves = script_moves(EXAMPLE).unwrap();
+        assert_eq!(Ok(Position{horizontal: 15, depth: 10, aim: 0}), Submarine::new(Simple).run(&moves));
+        assert_eq!(Ok(Position{horizontal: 15, depth: 60, aim: 10}), Submarine::new(Aimed).run(&moves));
+    }
+
+    /// Forward moves build up speed, which carries the submarine on after
+    /// every move, halving each time
+    struct Momentum
+    {
+        speed: i32,
+    }
+
+    impl MovementModel for Momentum
+    {
+        fn apply<N: Number>(self: &mut Self, position: &mut Position<N>, m: &Move) -> Result<(), Overflow>
+        {
+            match m.direction {
+                Direction::FORWARD => self.speed += m.stepsize,
+                _ => Simple.apply(position, m)?,
+            }
+            position.horizontal = (Checked(position.horizontal) + Checked::from(self.speed))?.0;
+            self.speed /= 2;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn custom_model()
+    {
+        let moves = script_moves("forward 4\ndown 2\ndown 1\n").unwrap();
+        let mut submarine = Submarine::new(Momentum{speed: 0});
+        submarine.execute(&moves[0]).unwrap();
+        assert_eq!(4, submarine.position().horizontal);
+        // Coasting 2 then 1
+        assert_eq!(Ok(Position{horizontal: 7, depth: 3, aim: 0}), submarine.run(&moves[1..]));
+    }
+
+    #[test]
+    fn record_track()
+    {
+        let moves = script_moves(EXAMPLE).unwrap();
+        let track = Track::record(Submarine::new(Aimed), &moves).unwrap();
+        assert_eq!(6, track.steps.len());
+        assert_eq!((2, moves[1].clone(), Position{horizontal: 5, depth: 0, aim: 5}), track.steps[1]);
+        assert_eq!(Some((6, 60)), track.max_depth());
+        assert!(track.surfacing().is_empty());
+        assert_eq!(None, Track::record(Submarine::new(Aimed), &[]).unwrap().max_depth());
+
+        let trajectory: Vec<_> = Submarine::new(Simple).trajectory(&moves).map(|s| s.map(|(step, _, p)| (step, p.depth)).unwrap()).collect();
+        assert_eq!(vec![(1, 0), (2, 5), (3, 5), (4, 2), (5, 10), (6, 10)], trajectory);
+    }
+
+    #[test]
+    fn surfacing()
+    {
+        let moves = script_moves("down 2\nup 3\nforward 1\ndown 1\nup 4\n").unwrap();
+        let track = Track::record(Submarine::new(Simple), &moves).unwrap();
+        let surfaced: Vec<_> = track.surfacing().iter().map(|&(step, p)| (step, p.depth)).collect();
+        assert_eq!(vec![(2, -1), (3, -1), (5, -4)], surfaced);
+        assert_eq!(Some((1, 2)), track.max_depth());
+    }
+
+    #[test]
+    fn export_track()
+    {
+        let moves = script_moves(EXAMPLE).unwrap();
+        let track = Track::record(Submarine::new(Simple), &moves).unwrap();
+        let csv = track.to_csv();
+        assert!(csv.starts_with("step,move,horizontal,depth,aim\n1,forward 5,5,0,0\n2,down 5,5,5,0\n"));
+        assert_eq!(7, csv.lines().count());
+
+        // Squeezed to 5 by 3
+        assert_eq!("S#~~~\n   #\n    #\n", Track::record(Submarine::new(Aimed), &moves).unwrap().side_view(5, 3));
+
+        let moves = script_moves("forward 2\ndown 2\nforward 2\nup 3\nforward 1\n").unwrap();
+        assert_eq!(
+            "    ##\nS~#~~~\n\n  # #\n",
+            Track::record(Submarine::new(Simple), &moves).unwrap().side_view(10, 10)
+        );
+
+        // The span of the full i128 range does not fit a u128
+        let corner = |v| Position{horizontal: v, depth: v, aim: 0};
+        let full = Track{start: corner(i128::MIN), steps: vec![(1, moves[0].clone(), corner(i128::MAX))]};
+        assert_eq!("S\n~~~\n  #\n", full.side_view(3, 3));
+    }
+
+    const SCRIPT: &str = "\
+# Survey run
+forward 5   # leave the dock
+repeat 2 {
+    down 3
+    repeat 3 { forward 1 }
+    back 1
+}
+surface
+";
+
+    #[test]
+    fn parse_program()
+    {
+        let program = parse_input(SCRIPT, &Context::default()).unwrap();
+        let forward = |n| Move{direction: Direction::FORWARD, stepsize: n};
+        assert_eq!(
+            Program{statements: vec![
+                Statement::MOVE(forward(5)),
+                Statement::REPEAT(2, vec![
+                    Statement::MOVE(Move{direction: Direction::DOWN, stepsize: 3}),
+                    Statement::REPEAT(3, vec![Statement::MOVE(forward(1))]),
+                    Statement::MOVE(Move{direction: Direction::BACK, stepsize: 1}),
+                ]),
+                Statement::MOVE(Move{direction: Direction::SURFACE, stepsize: 0}),
+            ]},
+            program
+        );
+        assert_eq!(12, program.moves().count());
+        assert_eq!("surface", program.moves().last().unwrap().to_string());
+        assert_eq!(Ok(Program::default()), parse_input("# nothing\n\n", &Context::default()));
+        assert_eq!(3, parse_input("repeat 2\n{\n    up 1 } down 1\n", &Context::default()).unwrap().moves().count());
+    }
+
+    #[test]
+    fn run_program()
+    {
+        let program = parse_input(SCRIPT, &Context::default()).unwrap();
+        let mut simple = Submarine::new(Simple);
+        simple.run(program.moves().take(11)).unwrap();
+        assert_eq!(Position{horizontal: 9, depth: 6, aim: 0}, simple.position());
+        assert_eq!(Ok(Position{horizontal: 9, depth: 0, aim: 0}), Submarine::new(Simple).run(program.moves()));
+
+        // Aim 3: 3 forward and 1 back dive 6, aim 6: another 12
+        let mut aimed = Submarine::new(Aimed);
+        aimed.run(program.moves().take(11)).unwrap();
+        assert_eq!(Position{horizontal: 9, depth: 18, aim: 6}, aimed.position());
+        assert_eq!(Ok(Position{horizontal: 9, depth: 0, aim: 0}), Submarine::new(Aimed).run(program.moves()));
+    }
+
+    #[test]
+    fn program_errors()
+    {
+        let error = |s: &str| {
+            let e = parse_input(s, &Context::default()).unwrap_err();
+            (e.line, e.column, e.expected)
+        };
+        assert_eq!((Some(3), 4, "up, down, forward, back or surface".into()), error("up 1\n\n    sideways 2\n"));
+        assert_eq!((Some(1), 8, "a step size".into()), error("forward 1x"));
+        assert_eq!((Some(1), 7, "a step size".into()), error("forward # 1"));
+        assert_eq!((Some(2), 8, "end of line".into()), error("up 1\nsurface 3"));
+        assert_eq!((Some(2), 9, "a matching }".into()), error("down 1\nrepeat 2 {\n  up 1\n"));
+        assert_eq!((Some(1), 0, "a command".into()), error("}"));
+        assert_eq!((Some(1), 7, "a repeat count".into()), error("repeat -1 { up 1 }"));
+        assert_eq!((Some(1), 9, "{".into()), error("repeat 2 up 1"));
+        assert_eq!((Some(2), 0, "{".into()), error("repeat 2\n\n"));
+        assert_eq!((Some(1), 10, "end of line".into()), error("forward 1 { up 1 }"));
+    }
+
+    #[test]
+    fn skip_malformed_commands()
+    {
+        let lenient = Context::new(parse::Mode::Lenient);
+        let program = parse_input("up 1\nrepeat 2 {\n  dive 3\n  down 2\n}\nback\n", &lenient);
+        assert_eq!(vec![Some(3), Some(6)], lenient.into_skipped().iter().map(|e| e.line).collect::<Vec<_>>());
+        assert_eq!(Ok(Position{horizontal: 0, depth: 3, aim: 0}), Submarine::new(Simple).run(program.unwrap().moves()));
+
+        // The closing brace of a block is not part of the skipped command
+        let lenient = Context::new(parse::Mode::Lenient);
+        let program = parse_input("repeat 2 { forward x }
+up 1
+", &lenient).unwrap();
+        assert_eq!(vec![Some(1)], lenient.into_skipped().iter().map(|e| e.line).collect::<Vec<_>>());
+        assert_eq!(vec![Statement::REPEAT(2, vec![]), Statement::MOVE(Move{direction: Direction::UP, stepsize: 1})], program.statements);
+    }
+
+    #[test]
+    fn unrolling_limit()
+    {
+        let program = parse_input(SCRIPT, &Context::default()).unwrap();
+        assert_eq!(Some(12), program.move_count());
+
+        let nested = parse_input("repeat 4000000000 { repeat 4000000000 { forward 1 } }
+", &Context::default()).unwrap();
+        assert_eq!(Some(16_000_000_000_000_000_000), nested.move_count());
+        let err = Puzzle::part1(&nested, &Values::new()).unwrap_err();
+        assert_eq!("The script unrolls to more than 100000000 moves", err.to_string());
+        let deeper = parse_input("repeat 4000000000 { repeat 4000000000 { repeat 4000000000 { up 1 } } }
+", &Context::default()).unwrap();
+        assert_eq!(None, deeper.move_count());
+        assert!(Puzzle::part2(&deeper, &Values::new()).is_err());
+    }
+
+    #[test]
+    fn overflow()
+    {
+        let moves = script_moves("down 2000000000\nforward 2\nforward 2000000000\n").unwrap();
+        let mut narrow = Submarine::starting_at(Aimed, Position::<i32>::new());
+        let err = narrow.run(&moves).unwrap_err();
+        assert_eq!(OverflowError{step: 2, command: moves[1].clone()}, err);
+        assert_eq!("Overflow at step 2 (forward 2)", err.to_string());
+        assert_eq!(Position{horizontal: 0, depth: 0, aim: 2_000_000_000}, narrow.position());
+        assert_eq!(2, Submarine::starting_at(Aimed, Position::<i32>::new()).trajectory(&moves).count());
+        assert_eq!(Err(err), Track::record(Submarine::starting_at(Aimed, Position::<i32>::new()), &moves));
+
+        let wide = Submarine::new(Aimed).run(&moves).unwrap();
+        assert_eq!(4_000_000_004_000_000_000, wide.depth);
+        assert_eq!(Err(Overflow), wide.metric());
+        let huge = Submarine::starting_at(Aimed, Position::<i128>::new()).run(&moves).unwrap();
+        assert_eq!(Ok(2_000_000_002 * 4_000_000_004_000_000_000), huge.metric());
+        assert_eq!(Err(Overflow), Number::checked_add(i64::MAX, 1));
+
+        let checked = Submarine::starting_at(Aimed, Position::<Checked<i64>>::new()).run(&moves).unwrap();
+        assert_eq!(Checked(wide.depth), checked.depth);
+        assert_eq!(Err(Overflow), checked.metric());
+        assert_eq!(Err(Overflow), Checked(i64::MAX) + Checked(1));
+        assert_eq!(Ok(Checked(-6)), Checked(2i32) * Checked(-3));
+    }
+
+    #[test]
+    fn metric_overflow()
+    {
+        // An aim of 2e9 dives 4e18 with every move, the final metric is about 3.2e38
+        let program = parse_input("down 2000000000\nrepeat 200000 { forward 2000000000 }\n", &Context::default()).unwrap();
+        assert_eq!(Ok(Answer::from(400_000_000_000_000i128 * 2_000_000_000)), Puzzle::part1(&program, &Values::new()));
+        let err = Puzzle::part2(&program, &Values::new()).unwrap_err();
+        assert_eq!("Overflow in the metric of the final position", err.to_string());
     }
-    position.metric()
 }
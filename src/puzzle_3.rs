@@ -1,20 +1,72 @@
+use std::cell::Cell;
+use std::fmt;
+use std::io::{self, BufRead};
 
-const INPUT: &str = include_str!("../data/3/input");
-const DIAGNOSTIC_WIDTH: usize = 12; // Power diagnostics are 12 bit
+use crate::params::{Param, Values};
+use crate::solution::{Answer, Context, ParseError, Solution, SolveError};
+
+const FAST_WIDTH: usize = 32;       // Widths up to this use the const generic `Diagnostic`
+const MAX_WIDTH: usize = 64;
+
+pub const WIDTH: Param = Param{
+    name: "width", description: "bits per diagnostic number, 0 infers it from the input", default: 0, range: 0..=MAX_WIDTH,
+};
+
+pub const PARAMS: &[Param] = &[WIDTH];
+
+/// Call the const generic `$f::<W>` for the runtime width `$width`
+macro_rules! with_width {
+    ($width:expr, $f:ident($arg:expr)) => {
+        with_width!(@widths $width, $f, $arg;
+            1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32)
+    };
+    (@widths $width:expr, $f:ident, $arg:expr; $($w:literal)*) => {{
+        let arg = $arg;
+        match $width {
+            $($w => $f::<$w>(arg),)*
+            w => panic!("Unsupported diagnostic width {}", w),
+        }
+    }};
+}
 
 #[derive(Debug, Clone)]
-struct Diagnostic<const WIDTH: usize> 
+pub struct Diagnostic<const WIDTH: usize> 
 {
     data: Vec<u32>,
     bitcount: [u32; WIDTH],
 }
 
-fn parse_input(s: &str) -> Vec<u32>
+/// A binary number of `width` bits, any width up to `MAX_WIDTH` if `None`
+fn parse_binary(s: &str, width: Option<usize>) -> Result<u64, ParseError>
 {
-    s
-        .split('\n') // Lines
-        .filter_map(|item| u32::from_str_radix(item, 2).ok()) // Parse binary
-        .collect() // collect the u32
+    let expected = || match width {
+        Some(width) => format!("a {} bit binary number", width),
+        None => format!("a binary number of at most {} bits", MAX_WIDTH),
+    };
+    if let Some(pos) = s.find(|c| c != '0' && c != '1') {
+        return Err(ParseError::at(s, &s[pos..], expected()));
+    }
+    let max = width.unwrap_or(MAX_WIDTH);
+    if s.len() > max || width.is_some_and(|w| s.len() != w) {
+        return Err(ParseError::at(s, &s[s.len().min(max)..], expected()));
+    }
+    u64::from_str_radix(s, 2).map_err(|_| ParseError::new(s, expected()))
+}
+
+/// All numbers must have the same width, which is taken from the first
+/// number unless `width` is given
+fn parse_input(s: &str, width: Option<usize>, context: &Context) -> Result<Readings, ParseError>
+{
+    let width = Cell::new(width);
+    let data = context.lines(s, |line| {
+        let value = parse_binary(line, width.get())?;
+        width.set(Some(line.len()));
+        Ok(value)
+    })?;
+    if data.is_empty() {
+        return Err(ParseError::new(s.trim(), "at least one binary number"));
+    }
+    Ok(Readings{ width: width.get().unwrap_or(0), data })
 }
 
 impl<const WIDTH: usize> Diagnostic<WIDTH>
@@ -33,7 +85,7 @@ impl<const WIDTH: usize> Diagnostic<WIDTH>
         {
             if (val & 1<<bit) != 0
             {
-                self.bitcount[bit as usize] += 1;
+                self.bitcount[bit] += 1;
             }
         }
         self.data.push(val);
@@ -45,27 +97,14 @@ impl<const WIDTH: usize> Diagnostic<WIDTH>
         let ones = self.bitcount[idx];
         let zeros = total - ones;
         let common = (ones >= zeros) as u32;
-        let cbit = common << idx;
-        cbit
+        common << idx
     }
 
     fn common(self: &Self) -> u32
     {
-        (0..WIDTH).into_iter().map(|i| self.common_bit(i)).sum()
-    }
-
-    fn filter_by_common_bit(self: Self, idx: usize) -> Self 
-    {
-        let cbit = self.common_bit(idx);
-        self.data.into_iter().filter(|it| (it & 1<<idx) == cbit).collect()
+        (0..WIDTH).map(|i| self.common_bit(i)).sum()
     }
 
-    fn filter_by_uncommon_bit(self: Self, idx: usize) -> Self 
-    {
-        let cbit = self.common_bit(idx);
-        self.data.into_iter().filter(|it| (it & 1<<idx) != cbit).collect()
-    }
-    
     fn len(self: &Self) -> usize
     {
         self.data.len()
@@ -90,48 +129,594 @@ fn gamma<const WIDTH: usize>(diagnostic: &Diagnostic::<WIDTH>) -> u32
     diagnostic.common()
 }
 
-fn gamma_to_epsilon(gamma: u32) -> u32
+fn gamma_to_epsilon<const WIDTH: usize>(gamma: u32) -> u32
+{
+    !gamma & (u32::MAX >> (32 - WIDTH))
+}
+
+fn calculate_power_consumption<const WIDTH: usize>(diagnostic: &Diagnostic::<WIDTH>) -> u64
 {
-    !gamma & (1<<DIAGNOSTIC_WIDTH)-1
+    let gamma = gamma(diagnostic);
+    let epsilon = gamma_to_epsilon::<WIDTH>(gamma);
+    gamma as u64 * epsilon as u64
 }
 
-pub fn calculate_power_consumption() -> u32
+fn fast_rating<const WIDTH: usize>(diagnostic: &Diagnostic::<WIDTH>, criteria: &RatingCriteria) -> u32
 {
-    let data = parse_input(INPUT);
-    let diagnostic = data.into_iter().collect::<Diagnostic::<DIAGNOSTIC_WIDTH>>();
-    let gamma = gamma(&diagnostic);
-    let epsilon = gamma_to_epsilon(gamma);
-    gamma * epsilon 
+    let values = diagnostic.data.iter().map(|&v| v as u64).collect();
+    filter(values, WIDTH, criteria, None)[0] as u32
 }
 
 fn oxygen_rating<const WIDTH: usize>(diagnostic: Diagnostic::<WIDTH>) -> u32
 {
-    let mut d = diagnostic;
-    for i in (0..WIDTH).rev() {
-        if d.len() == 1 { break }
-        d = d.filter_by_common_bit(i);
-    }
-    d.data[0]
+    fast_rating(&diagnostic, &RatingCriteria::OXYGEN)
 }
 
 fn co2_rating<const WIDTH: usize>(diagnostic: Diagnostic::<WIDTH>) -> u32
 {
-    let mut d = diagnostic;
-    for i in (0..WIDTH).rev() {
-        if d.len() == 1 { break }
-        d = d.filter_by_uncommon_bit(i);
-    }
-    d.data[0]
+    fast_rating(&diagnostic, &RatingCriteria::CO2)
 }
 
-pub fn calculate_life_rating() -> u32
+fn calculate_life_rating<const WIDTH: usize>(diagnostic: &Diagnostic::<WIDTH>) -> u64
 {
-    let data = parse_input(INPUT);
-    let diagnostic = data.into_iter().collect::<Diagnostic::<DIAGNOSTIC_WIDTH>>();
     let ox_rating = oxygen_rating(diagnostic.clone());
-    let co2_rating = co2_rating(diagnostic);
+    let co2_rating = co2_rating(diagnostic.clone());
+
+    ox_rating as u64 * co2_rating as u64
+}
 
-    ox_rating*co2_rating
+/// A diagnostic whose width is only known at runtime, up to 64 bits
+#[derive(Debug, Clone)]
+pub struct DynDiagnostic
+{
+    width: usize,
+    data: Vec<u64>,
+    bitcount: Vec<u32>,
+}
+
+impl DynDiagnostic
+{
+    /// Panics unless `width` is in 1..=64
+    pub fn new(width: usize) -> Self
+    {
+        assert!((1..=MAX_WIDTH).contains(&width), "Unsupported diagnostic width {}", width);
+        DynDiagnostic {
+            width,
+            data: Vec::new(),
+            bitcount: vec![0; width],
+        }
+    }
+
+    /// The bits of the values above `width` are dropped, as with `push`
+    pub fn from_values<I: IntoIterator<Item=u64>>(width: usize, values: I) -> Self
+    {
+        let mut diagnostic = DynDiagnostic::new(width);
+        for value in values {
+            diagnostic.push(value);
+        }
+        diagnostic
+    }
+
+    /// Add a value, masked to the lowest `width` bits so that the sorted
+    /// values still group by their leading bits
+    pub fn push(self: &mut Self, val: u64)
+    {
+        let val = val & self.mask();
+        for bit in 0..self.width {
+            if (val & 1<<bit) != 0 {
+                self.bitcount[bit] += 1;
+            }
+        }
+        self.data.push(val);
+    }
+
+    pub fn width(self: &Self) -> usize
+    {
+        self.width
+    }
+
+    pub fn len(self: &Self) -> usize
+    {
+        self.data.len()
+    }
+
+    pub fn is_empty(self: &Self) -> bool
+    {
+        self.data.is_empty()
+    }
+
+    fn mask(self: &Self) -> u64
+    {
+        u64::MAX >> (MAX_WIDTH - self.width)
+    }
+
+    fn common_bit(self: &Self, idx: usize) -> u64
+    {
+        let ones = self.bitcount[idx] as usize;
+        ((ones >= self.len() - ones) as u64) << idx
+    }
+
+    fn counts(self: &Self, idx: usize) -> (usize, usize)
+    {
+        let ones = self.bitcount[idx] as usize;
+        (ones, self.len() - ones)
+    }
+
+    pub fn gamma(self: &Self) -> u64
+    {
+        (0..self.width).map(|i| self.common_bit(i)).sum()
+    }
+
+    pub fn epsilon(self: &Self) -> u64
+    {
+        !self.gamma() & self.mask()
+    }
+
+    pub fn power_consumption(self: &Self) -> u128
+    {
+        self.gamma() as u128 * self.epsilon() as u128
+    }
+
+    fn rating_trace(self: &Self, criteria: &RatingCriteria) -> Option<(u64, Vec<FilterRound>)>
+    {
+        let mut trace = Vec::new();
+        let survivors = filter(self.data.clone(), self.width, criteria, Some(&mut trace));
+        Some((*survivors.first()?, trace))
+    }
+
+    /// Bit statistics and filter traces explaining both answers, `None` for
+    /// an empty diagnostic
+    pub fn report(self: &Self) -> Option<DiagnosticReport>
+    {
+        let gamma = self.gamma();
+        let epsilon = self.epsilon();
+        let bits = (0..self.width)
+            .rev()
+            .map(|bit| {
+                let (ones, zeros) = self.counts(bit);
+                BitStats{ bit, ones, zeros, gamma: (gamma >> bit & 1) as u8, epsilon: (epsilon >> bit & 1) as u8 }
+            })
+            .collect();
+        let (oxygen_rating, oxygen_trace) = self.rating_trace(&RatingCriteria::OXYGEN)?;
+        let (co2_rating, co2_trace) = self.rating_trace(&RatingCriteria::CO2)?;
+        Some(DiagnosticReport{
+            width: self.width,
+            count: self.len(),
+            bits,
+            gamma,
+            epsilon,
+            oxygen_rating,
+            oxygen_trace,
+            co2_rating,
+            co2_trace,
+        })
+    }
+
+    /// `None` for an empty diagnostic, as are the other ratings
+    pub fn oxygen_rating(self: &Self) -> Option<u64>
+    {
+        rate(self, &RatingCriteria::OXYGEN).first().copied()
+    }
+
+    pub fn co2_rating(self: &Self) -> Option<u64>
+    {
+        rate(self, &RatingCriteria::CO2).first().copied()
+    }
+
+    pub fn life_rating(self: &Self) -> Option<u128>
+    {
+        Some(self.oxygen_rating()? as u128 * self.co2_rating()? as u128)
+    }
+}
+
+/// The bit counts of diagnostic numbers seen one at a time, which is all the
+/// power consumption needs: reports too large to keep in memory are read
+/// with `BitCounter::read`
+#[derive(Debug, Clone)]
+pub struct BitCounter
+{
+    width: Option<usize>,
+    count: usize,
+    ones: [u64; MAX_WIDTH],
+}
+
+impl Default for BitCounter
+{
+    fn default() -> Self
+    {
+        BitCounter::new()
+    }
+}
+
+impl BitCounter
+{
+    /// The width is that of the first number
+    pub fn new() -> Self
+    {
+        BitCounter {
+            width: None,
+            count: 0,
+            ones: [0; MAX_WIDTH],
+        }
+    }
+
+    /// Only accept numbers of `width` bits. Panics if it is 0 or more than 64.
+    pub fn with_width(width: usize) -> Self
+    {
+        assert!((1..=MAX_WIDTH).contains(&width), "Unsupported diagnostic width {}", width);
+        BitCounter { width: Some(width), ..BitCounter::new() }
+    }
+
+    pub fn push(self: &mut Self, s: &str) -> Result<(), ParseError>
+    {
+        let value = parse_binary(s, self.width)?;
+        self.width = Some(s.len());
+        for bit in 0..s.len() {
+            if (value & 1<<bit) != 0 {
+                self.ones[bit] += 1;
+            }
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Count the numbers of `reader`, one per line, holding a single line in
+    /// memory. Malformed numbers fail with `InvalidData`.
+    pub fn read<R: BufRead>(mut reader: R) -> io::Result<Self>
+    {
+        let invalid = |e: ParseError| io::Error::new(io::ErrorKind::InvalidData, e.day(3).to_string());
+        let mut counter = BitCounter::new();
+        let mut buffer = String::new();
+        let mut line = 0;
+        while reader.read_line(&mut buffer)? > 0 {
+            line += 1;
+            let text = buffer.trim_end_matches(['\n', '\r']);
+            let number = text.trim();
+            if !number.is_empty() {
+                counter.push(number).map_err(|e| invalid(e.within(text, number).line(line)))?;
+            }
+            buffer.clear();
+        }
+        if counter.count == 0 {
+            return Err(invalid(ParseError::new("", "at least one binary number")));
+        }
+        Ok(counter)
+    }
+
+    pub fn width(self: &Self) -> usize
+    {
+        self.width.unwrap_or(0)
+    }
+
+    pub fn len(self: &Self) -> usize
+    {
+        self.count
+    }
+
+    pub fn is_empty(self: &Self) -> bool
+    {
+        self.count == 0
+    }
+
+    /// Ties count as a most common 1, as in `DynDiagnostic`
+    pub fn gamma(self: &Self) -> u64
+    {
+        (0..self.width())
+            .filter(|&bit| self.ones[bit] as usize * 2 >= self.count)
+            .map(|bit| 1 << bit)
+            .sum()
+    }
+
+    pub fn epsilon(self: &Self) -> u64
+    {
+        !self.gamma() & (u64::MAX >> (MAX_WIDTH - self.width().max(1)))
+    }
+
+    pub fn power_consumption(self: &Self) -> u128
+    {
+        self.gamma() as u128 * self.epsilon() as u128
+    }
+}
+
+/// Which bit value a filter round keeps
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Common {
+    MOST,
+    LEAST,
+}
+
+/// The bit value kept when ones and zeros are equally common
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Tie {
+    ZERO,
+    ONE,
+}
+
+/// The order in which the bits are filtered on
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Scan {
+    MSB,    // From the most significant bit down
+    LSB,    // From the least significant bit up
+}
+
+/// When filtering stops
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Until {
+    ONE,            // A single number is left
+    LEFT(usize),    // At most this many numbers are left
+}
+
+/// How a rating filters the numbers of a diagnostic down to its value
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RatingCriteria {
+    pub keep: Common,
+    pub tie: Tie,
+    pub scan: Scan,
+    pub until: Until,
+}
+
+impl RatingCriteria {
+    pub const OXYGEN: Self = RatingCriteria{ keep: Common::MOST, tie: Tie::ONE, scan: Scan::MSB, until: Until::ONE };
+    pub const CO2: Self = RatingCriteria{ keep: Common::LEAST, tie: Tie::ZERO, scan: Scan::MSB, until: Until::ONE };
+
+    /// The bit value kept among `ones` ones and `zeros` zeros
+    fn kept(self: &Self, ones: usize, zeros: usize) -> u8 {
+        if ones == zeros {
+            return match self.tie { Tie::ZERO => 0, Tie::ONE => 1 };
+        }
+        match self.keep {
+            Common::MOST => (ones > zeros) as u8,
+            Common::LEAST => (ones < zeros) as u8,
+        }
+    }
+
+    fn done(self: &Self, left: usize) -> bool {
+        match self.until {
+            Until::ONE => left <= 1,
+            Until::LEFT(n) => left <= n,
+        }
+    }
+
+    /// Sort key putting numbers sharing their first scanned bits next to
+    /// each other: the value itself, or its bits reversed for an LSB scan
+    fn key(self: &Self, v: u64, width: usize) -> u64 {
+        match self.scan {
+            Scan::MSB => v,
+            Scan::LSB => v.reverse_bits() >> (MAX_WIDTH - width),
+        }
+    }
+
+    fn bits(self: &Self, width: usize) -> Box<dyn Iterator<Item=usize>> {
+        match self.scan {
+            Scan::MSB => Box::new((0..width).rev()),
+            Scan::LSB => Box::new(0..width),
+        }
+    }
+}
+
+/// Filter `values` of `width` bits as `criteria` says, recording each round
+/// in `trace`. When all numbers share a bit that round keeps them all, even
+/// if the criteria ask for the least common value.
+///
+/// The values are sorted once in scan order, so the numbers sharing the bits
+/// scanned so far are a contiguous range and each round only splits that
+/// range with a binary search. The survivors are returned in ascending order.
+fn filter(values: Vec<u64>, width: usize, criteria: &RatingCriteria, mut trace: Option<&mut Vec<FilterRound>>) -> Vec<u64>
+{
+    let mut sorted = values;
+    sorted.sort_unstable_by_key(|&v| criteria.key(v, width));
+    let (mut start, mut end) = (0, sorted.len());
+    for bit in criteria.bits(width) {
+        if criteria.done(end - start) { break }
+        let split = start + sorted[start..end].partition_point(|&v| v & 1<<bit == 0);
+        let (zeros, ones) = (split - start, end - split);
+        let kept = if ones == 0 || zeros == 0 { (ones != 0) as u8 } else { criteria.kept(ones, zeros) };
+        if kept == 1 { start = split } else { end = split }
+        if let Some(trace) = trace.as_mut() {
+            trace.push(FilterRound{ bit, ones, zeros, kept, survivors: ascending(&sorted[start..end]) });
+        }
+    }
+    ascending(&sorted[start..end])
+}
+
+fn ascending(values: &[u64]) -> Vec<u64>
+{
+    let mut values = values.to_vec();
+    values.sort_unstable();
+    values
+}
+
+/// The numbers of `diagnostic` left after filtering them by `criteria`, in
+/// ascending order. More than `criteria.until` allows are left if the bits
+/// run out first, e.g. with duplicates.
+pub fn rate(diagnostic: &DynDiagnostic, criteria: &RatingCriteria) -> Vec<u64>
+{
+    filter(diagnostic.data.clone(), diagnostic.width, criteria, None)
+}
+
+/// The counts of one bit position over all numbers of a diagnostic
+#[derive(Debug, PartialEq, Clone)]
+pub struct BitStats {
+    pub bit: usize,     // 0 is the least significant bit
+    pub ones: usize,
+    pub zeros: usize,
+    pub gamma: u8,
+    pub epsilon: u8,
+}
+
+impl BitStats {
+    /// Ties count as a most common 1, so gamma gets a 1 and epsilon a 0
+    pub fn tie(self: &Self) -> bool {
+        self.ones == self.zeros
+    }
+}
+
+/// One round of a rating filter: the numbers whose `bit` equals `kept`
+/// survive
+#[derive(Debug, PartialEq, Clone)]
+pub struct FilterRound {
+    pub bit: usize,
+    pub ones: usize,
+    pub zeros: usize,
+    pub kept: u8,
+    pub survivors: Vec<u64>,
+}
+
+impl FilterRound {
+    pub fn tie(self: &Self) -> bool {
+        self.ones == self.zeros
+    }
+}
+
+/// Everything that went into the power consumption and life rating of a
+/// diagnostic. Bits are listed from the most significant one.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DiagnosticReport {
+    pub width: usize,
+    pub count: usize,
+    pub bits: Vec<BitStats>,
+    pub gamma: u64,
+    pub epsilon: u64,
+    pub oxygen_rating: u64,
+    pub oxygen_trace: Vec<FilterRound>,
+    pub co2_rating: u64,
+    pub co2_trace: Vec<FilterRound>,
+}
+
+/// Survivors are only listed in the table up to this many
+const LISTED_SURVIVORS: usize = 4;
+
+impl DiagnosticReport {
+    fn binary(self: &Self, v: u64) -> String {
+        format!("{:0width$b}", v, width = self.width)
+    }
+
+    fn trace_json(self: &Self, trace: &[FilterRound]) -> String {
+        let rounds: Vec<String> = trace
+            .iter()
+            .map(|r| {
+                let survivors: Vec<String> = r.survivors.iter().map(|&v| format!("\"{}\"", self.binary(v))).collect();
+                format!(
+                    "{{\"bit\": {}, \"ones\": {}, \"zeros\": {}, \"tie\": {}, \"kept\": {}, \"survivors\": [{}]}}",
+                    r.bit, r.ones, r.zeros, r.tie(), r.kept, survivors.join(", ")
+                )
+            })
+            .collect();
+        format!("[{}]", rounds.join(", "))
+    }
+
+    /// A single line JSON object, numbers are given as binary strings
+    pub fn to_json(self: &Self) -> String {
+        let bits: Vec<String> = self.bits
+            .iter()
+            .map(|b| format!(
+                "{{\"bit\": {}, \"ones\": {}, \"zeros\": {}, \"tie\": {}, \"gamma\": {}, \"epsilon\": {}}}",
+                b.bit, b.ones, b.zeros, b.tie(), b.gamma, b.epsilon
+            ))
+            .collect();
+        format!(
+            "{{\"width\": {}, \"count\": {}, \"bits\": [{}], \"gamma\": \"{}\", \"epsilon\": \"{}\", \
+             \"oxygen_rating\": \"{}\", \"oxygen_trace\": {}, \"co2_rating\": \"{}\", \"co2_trace\": {}}}",
+            self.width, self.count, bits.join(", "), self.binary(self.gamma), self.binary(self.epsilon),
+            self.binary(self.oxygen_rating), self.trace_json(&self.oxygen_trace),
+            self.binary(self.co2_rating), self.trace_json(&self.co2_trace)
+        )
+    }
+
+    fn fmt_trace(self: &Self, f: &mut fmt::Formatter, name: &str, rating: u64, trace: &[FilterRound]) -> fmt::Result {
+        writeln!(f, "{} rating {} ({})", name, self.binary(rating), rating)?;
+        writeln!(f, "{:>5} {:>5} {:>5} {:>3} {:>4}  survivors", "bit", "ones", "zeros", "tie", "kept")?;
+        for r in trace {
+            let mut survivors = r.survivors.len().to_string();
+            if r.survivors.len() <= LISTED_SURVIVORS {
+                let listed: Vec<String> = r.survivors.iter().map(|&v| self.binary(v)).collect();
+                survivors = format!("{} {}", survivors, listed.join(" "));
+            }
+            let tie = if r.tie() { "yes" } else { "" };
+            writeln!(f, "{:>5} {:>5} {:>5} {:>3} {:>4}  {}", r.bit, r.ones, r.zeros, tie, r.kept, survivors)?;
+        }
+        Ok(())
+    }
+}
+
+/// The bit statistics and both filter traces as tables
+impl fmt::Display for DiagnosticReport {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} numbers of {} bits", self.count, self.width)?;
+        writeln!(f, "{:>5} {:>5} {:>5} {:>3} {:>5} {:>7}", "bit", "ones", "zeros", "tie", "gamma", "epsilon")?;
+        for b in &self.bits {
+            let tie = if b.tie() { "yes" } else { "" };
+            writeln!(f, "{:>5} {:>5} {:>5} {:>3} {:>5} {:>7}", b.bit, b.ones, b.zeros, tie, b.gamma, b.epsilon)?;
+        }
+        writeln!(f, "gamma {} ({}), epsilon {} ({})", self.binary(self.gamma), self.gamma, self.binary(self.epsilon), self.epsilon)?;
+        self.fmt_trace(f, "oxygen", self.oxygen_rating, &self.oxygen_trace)?;
+        self.fmt_trace(f, "co2", self.co2_rating, &self.co2_trace)
+    }
+}
+
+/// The diagnostic numbers with their common width
+#[derive(Debug)]
+pub struct Readings {
+    width: usize,
+    data: Vec<u64>,
+}
+
+impl Readings {
+    fn fast<const WIDTH: usize>(self: &Self) -> Diagnostic<WIDTH>
+    {
+        self.data.iter().map(|&v| v as u32).collect()
+    }
+
+    fn dynamic(self: &Self) -> DynDiagnostic
+    {
+        DynDiagnostic::from_values(self.width, self.data.iter().copied())
+    }
+
+    pub fn report(self: &Self) -> Option<DiagnosticReport>
+    {
+        self.dynamic().report()
+    }
+}
+
+fn power_consumption<const WIDTH: usize>(readings: &Readings) -> u64
+{
+    calculate_power_consumption(&readings.fast::<WIDTH>())
+}
+
+fn life_rating<const WIDTH: usize>(readings: &Readings) -> u64
+{
+    calculate_life_rating(&readings.fast::<WIDTH>())
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Parsed = Readings;
+
+    fn parse(input: &str, params: &Values, context: &Context) -> Result<Self::Parsed, ParseError> {
+        parse_input(input, Some(WIDTH.get(params)).filter(|&w| w > 0), context)
+    }
+
+    fn part1(readings: &Self::Parsed, _params: &Values) -> Result<Answer, SolveError> {
+        if readings.width <= FAST_WIDTH {
+            Ok(with_width!(readings.width, power_consumption(readings)).into())
+        }
+        else {
+            Ok(readings.dynamic().power_consumption().into())
+        }
+    }
+
+    fn part2(readings: &Self::Parsed, _params: &Values) -> Result<Answer, SolveError> {
+        if readings.width <= FAST_WIDTH {
+            Ok(with_width!(readings.width, life_rating(readings)).into())
+        }
+        else {
+            readings.dynamic()
+                .life_rating()
+                .map(Answer::from)
+                .ok_or_else(|| SolveError("No diagnostic numbers to rate".into()))
+        }
+    }
 }
 
 #[cfg(test)]
@@ -139,6 +724,8 @@ mod test {
 
     use super::*;
 
+    const DIAGNOSTIC_WIDTH: usize = 12; // The puzzle input is 12 bit
+
     #[test]
     fn diagnostic_create() 
     {
@@ -163,10 +750,23 @@ mod test {
         assert_eq!(0b00111, d.common());
     }
 
+    const INPUT: &str = include_str!("../data/3/input");
+
+    #[test]
+    fn parse_diagnostic_error()
+    {
+        let err = parse_input("000000000000\n00000000100\n", None, &Context::default()).unwrap_err();
+        assert_eq!(Some(2), err.line);
+        assert_eq!(11, err.column);
+        let err = parse_input("000000020000", None, &Context::default()).unwrap_err();
+        assert_eq!(7, err.column);
+    }
+
     #[test]
     fn power_consumption()
     {
-        assert_eq!(3901196u32, calculate_power_consumption());
+        let diagnostic = parse_input(INPUT, None, &Context::default()).unwrap().fast::<DIAGNOSTIC_WIDTH>();
+        assert_eq!(3901196u64, calculate_power_consumption(&diagnostic));
     }
 
     const TEST_DATA: [u32; 12] = [
@@ -187,4 +787,196 @@ mod test {
         let diagnostic = TEST_DATA.into_iter().collect::<Diagnostic::<5>>();
         assert_eq!(0b01010, co2_rating(diagnostic));
     }
+
+    fn example_input() -> String
+    {
+        TEST_DATA.iter().map(|v| format!("{:05b}\n", v)).collect()
+    }
+
+    #[test]
+    fn infer_width()
+    {
+        let readings = Puzzle::parse(&example_input(), &Values::new(), &Context::default()).unwrap();
+        assert_eq!(5, readings.width);
+        assert_eq!(Ok(Answer::Integer(198)), Puzzle::part1(&readings, &Values::new()));
+        assert_eq!(Ok(Answer::Integer(230)), Puzzle::part2(&readings, &Values::new()));
+        assert_eq!(DIAGNOSTIC_WIDTH, Puzzle::parse(INPUT, &Values::new(), &Context::default()).unwrap().width);
+    }
+
+    #[test]
+    fn mixed_widths()
+    {
+        let err = parse_input("10110\n0111\n", None, &Context::default()).unwrap_err();
+        assert_eq!((Some(2), 4, "a 5 bit binary number"), (err.line, err.column, err.expected.as_str()));
+        let err = parse_input(&"1".repeat(65), None, &Context::default()).unwrap_err();
+        assert_eq!((64, "a binary number of at most 64 bits"), (err.column, err.expected.as_str()));
+        assert!(parse_input("\n", None, &Context::default()).is_err());
+    }
+
+    #[test]
+    fn width_param()
+    {
+        let values = Values::from([("width", 12)]);
+        assert!(Puzzle::parse(&example_input(), &values, &Context::default()).is_err());
+        let values = Values::from([("width", 5)]);
+        let readings = Puzzle::parse(&example_input(), &values, &Context::default()).unwrap();
+        assert_eq!(Ok(Answer::Integer(198)), Puzzle::part1(&readings, &values));
+    }
+
+    #[test]
+    fn dynamic_matches_fast_path()
+    {
+        let readings = parse_input(INPUT, None, &Context::default()).unwrap();
+        let fast = readings.fast::<DIAGNOSTIC_WIDTH>();
+        let dynamic = readings.dynamic();
+        assert_eq!(calculate_power_consumption(&fast) as u128, dynamic.power_consumption());
+        assert_eq!(Some(calculate_life_rating(&fast) as u128), dynamic.life_rating());
+    }
+
+    #[test]
+    fn wide_diagnostic()
+    {
+        // The example with 35 set bits appended: those are all most common
+        let low = (1u64 << 35) - 1;
+        let wide = DynDiagnostic::from_values(40, TEST_DATA.iter().map(|&v| (v as u64) << 35 | low));
+        assert_eq!((22 << 35) | low, wide.gamma());
+        assert_eq!(9 << 35, wide.epsilon());
+        assert_eq!(Some(0b10111 << 35 | low), wide.oxygen_rating());
+        assert_eq!(Some(0b01010 << 35 | low), wide.co2_rating());
+
+        let full = DynDiagnostic::from_values(64, [u64::MAX, u64::MAX, 1]);
+        assert_eq!(u64::MAX, full.gamma());
+        assert_eq!(0, full.epsilon());
+        let input = format!("{}\n{}\n", "1".repeat(64), "0".repeat(63) + "1");
+        let readings = Puzzle::parse(&input, &Values::new(), &Context::default()).unwrap();
+        assert_eq!(64, readings.width);
+        assert_eq!(Ok(Answer::Integer(0)), Puzzle::part1(&readings, &Values::new()));
+    }
+
+    #[test]
+    fn example_report()
+    {
+        let report = Puzzle::parse(&example_input(), &Values::new(), &Context::default()).unwrap().report().unwrap();
+        assert_eq!((5, 12, 22, 9), (report.width, report.count, report.gamma, report.epsilon));
+        assert_eq!((0b10111, 0b01010), (report.oxygen_rating, report.co2_rating));
+        assert_eq!(BitStats{ bit: 4, ones: 7, zeros: 5, gamma: 1, epsilon: 0 }, report.bits[0]);
+        assert!(!report.bits.iter().any(BitStats::tie));
+
+        // The last oxygen round and the second CO2 round are ties, decided towards 1 and 0
+        let last = report.oxygen_trace.last().unwrap();
+        assert!(last.tie() && last.kept == 1);
+        assert_eq!(vec![0b10111], last.survivors);
+        assert_eq!(3, report.co2_trace.len());
+        assert!(report.co2_trace[2].tie() && report.co2_trace[2].kept == 0);
+        assert_eq!(vec![0b01010, 0b01111], report.co2_trace[1].survivors);
+
+        let table = report.to_string();
+        assert!(table.starts_with("12 numbers of 5 bits\n  bit  ones zeros tie gamma epsilon\n    4     7     5         1       0\n"));
+        assert!(table.contains("gamma 10110 (22), epsilon 01001 (9)\n"));
+        assert!(table.contains("    0     1     1 yes    1  1 10111\n"));
+        assert!(table.contains("    4     7     5        0  5\n"));
+
+        let json = report.to_json();
+        assert!(json.starts_with("{\"width\": 5, \"count\": 12, \"bits\": [{\"bit\": 4, \"ones\": 7, \"zeros\": 5, \"tie\": false, \"gamma\": 1, \"epsilon\": 0}, "));
+        assert!(json.contains("\"co2_rating\": \"01010\", \"co2_trace\": [{\"bit\": 4, \"ones\": 7, \"zeros\": 5, \"tie\": false, \"kept\": 0, \"survivors\": [\"00010\", \"00100\", "));
+        assert!(json.ends_with("{\"bit\": 2, \"ones\": 1, \"zeros\": 1, \"tie\": true, \"kept\": 0, \"survivors\": [\"01010\"]}]}"));
+    }
+
+    #[test]
+    fn report_tie()
+    {
+        let report = DynDiagnostic::from_values(2, [0b10, 0b01]).report().unwrap();
+        assert!(report.bits.iter().all(BitStats::tie));
+        assert_eq!((0b11, 0b00), (report.gamma, report.epsilon));
+        assert_eq!((0b10, 0b01), (report.oxygen_rating, report.co2_rating));
+    }
+
+    #[test]
+    fn empty_diagnostic()
+    {
+        let empty = DynDiagnostic::new(5);
+        assert!(empty.is_empty());
+        assert_eq!((None, None, None), (empty.oxygen_rating(), empty.co2_rating(), empty.life_rating()));
+        assert_eq!(None, empty.report());
+        assert!(rate(&empty, &RatingCriteria::OXYGEN).is_empty());
+        assert_eq!(None, DynDiagnostic::from_values(3, []).life_rating());
+    }
+
+    #[test]
+    fn rating_criteria()
+    {
+        let example = DynDiagnostic::from_values(5, TEST_DATA.iter().map(|&v| v as u64));
+        assert_eq!(vec![0b10111], rate(&example, &RatingCriteria::OXYGEN));
+        assert_eq!(vec![0b01010], rate(&example, &RatingCriteria::CO2));
+
+        // Scanning from the least significant bit: the even numbers are the
+        // most common, then bits 2 and 3 are ties decided towards 1
+        let lsb = RatingCriteria{ scan: Scan::LSB, ..RatingCriteria::OXYGEN };
+        assert_eq!(vec![0b11110], rate(&example, &lsb));
+
+        // Stopping with up to two left, after the first two bits
+        let two = RatingCriteria{ until: Until::LEFT(2), ..RatingCriteria::CO2 };
+        assert_eq!(vec![0b01010, 0b01111], rate(&example, &two));
+        let many = RatingCriteria{ until: Until::LEFT(12), ..RatingCriteria::CO2 };
+        assert_eq!(12, rate(&example, &many).len());
+
+        let tie = DynDiagnostic::from_values(2, [0b10, 0b01]);
+        assert_eq!(vec![0b01], rate(&tie, &RatingCriteria{ tie: Tie::ZERO, ..RatingCriteria::OXYGEN }));
+        assert_eq!(vec![0b10], rate(&tie, &RatingCriteria{ tie: Tie::ONE, ..RatingCriteria::CO2 }));
+    }
+
+    #[test]
+    fn rating_shared_bits()
+    {
+        // All numbers share the top bit, even the least common value keeps them
+        let d = DynDiagnostic::from_values(3, [0b110, 0b101, 0b101]);
+        assert_eq!(vec![0b110], rate(&d, &RatingCriteria::CO2));
+        let dupes = DynDiagnostic::from_values(3, [0b101, 0b101]);
+        assert_eq!(vec![0b101, 0b101], rate(&dupes, &RatingCriteria::CO2));
+    }
+
+    #[test]
+    fn mask_wide_values()
+    {
+        // The 2 is 0 in one bit, the sort and the filter only see that bit
+        let d = DynDiagnostic::from_values(1, [0b10, 0b01]);
+        assert_eq!((1, 0), (d.gamma(), d.epsilon()));
+        assert_eq!(vec![0], rate(&d, &RatingCriteria::CO2));
+        assert_eq!(Some(0), d.life_rating());
+    }
+
+    #[test]
+    fn stream_bit_counts()
+    {
+        let counter = BitCounter::read(io::Cursor::new(example_input())).unwrap();
+        assert_eq!((5, 12), (counter.width(), counter.len()));
+        assert_eq!((22, 9), (counter.gamma(), counter.epsilon()));
+
+        let counter = BitCounter::read(INPUT.as_bytes()).unwrap();
+        assert_eq!(3901196, counter.power_consumption());
+        assert_eq!(parse_input(INPUT, None, &Context::default()).unwrap().dynamic().gamma(), counter.gamma());
+
+        let err = BitCounter::read("101\r\n\n  100\n10\n".as_bytes()).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert!(err.to_string().starts_with("Parse error in day 3 at line 4, column 3: expected a 3 bit binary number"));
+        assert!(BitCounter::read("\n".as_bytes()).is_err());
+
+        let mut counter = BitCounter::with_width(4);
+        assert!(counter.push("101").is_err());
+        counter.push("1010").unwrap();
+        assert_eq!((4, 1), (counter.width(), counter.len()));
+    }
+
+    #[test]
+    fn sorted_partition_scans()
+    {
+        // Sorting puts equal prefixes together for either scan direction
+        let values = [0b0110, 0b1011, 0b0011, 0b1110, 0b0111, 0b0011];
+        let d = DynDiagnostic::from_values(4, values);
+        let lsb = RatingCriteria{ scan: Scan::LSB, ..RatingCriteria::OXYGEN };
+        assert_eq!(vec![0b0110, 0b0111], rate(&d, &RatingCriteria{ until: Until::LEFT(2), ..RatingCriteria::OXYGEN }));
+        assert_eq!(vec![0b0011, 0b0011, 0b1011], rate(&d, &RatingCriteria{ until: Until::LEFT(3), ..lsb }));
+        assert_eq!(vec![0b0011, 0b0011], rate(&d, &lsb));
+        assert_eq!(vec![0b0110], rate(&d, &RatingCriteria{ scan: Scan::LSB, ..RatingCriteria::CO2 }));
+    }
 }
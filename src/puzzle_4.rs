@@ -1,30 +1,80 @@
-const INPUT_NUMBERS: &str = include_str!("../data/4/numbers");
-const INPUT_BOARDS: &str = include_str!("../data/4/boards");
+use crate::grid::{Grid, Pos};
+use crate::params::{Param, Values};
+use crate::parse::{self, Section};
+use crate::solution::{Answer, Context, ParseError, Solution, SolveError};
+
 const BOARD_SIZE : usize = 5;
 
-fn parse_numbers(s: &str) -> Vec<u32>
+pub const BOARD_ROWS: Param = Param{
+    name: "board_rows", description: "rows of each bingo board", default: BOARD_SIZE, range: 1..=100,
+};
+
+pub const BOARD_COLS: Param = Param{
+    name: "board_cols", description: "numbers in each board row", default: BOARD_SIZE, range: 1..=100,
+};
+
+pub const WIN_PATTERNS: Param = Param{
+    name: "win_patterns",
+    description: "winning patterns, the sum of 1 rows, 2 cols, 4 diagonal, 8 antidiagonal, 16 corners and 32 blackout",
+    default: 3,
+    range: 1..=63,
+};
+
+pub const PARAMS: &[Param] = &[BOARD_ROWS, BOARD_COLS, WIN_PATTERNS];
+
+fn parse_number(item: &str) -> Result<u32, ParseError>
 {
-    s
-        .split(',') // Lines
-        .filter_map(|item| item.parse::<u32>().ok()) // parse to i32 (if some)
-        .collect() // collect the i32
+    parse::token(item, item, "a drawn number")
+}
+
+fn parse_board(section: &Section, shape: (usize, usize), context: &Context) -> Result<Board, ParseError>
+{
+    let (height, width) = shape;
+    let rows: Vec<Vec<u32>> = section.lines(context, |line| {
+        let row = line
+            .split_whitespace()
+            .map(|item| parse::token(line, item, "a board number"))
+            .collect::<Result<Vec<u32>, ParseError>>()?;
+        if row.len() != width {
+            let at = line.split_whitespace().nth(width).unwrap_or(&line[line.len()..]);
+            return Err(ParseError::at(line, at, format!("{} numbers per row", width)));
+        }
+        Ok(row)
+    })?;
+    if rows.len() != height {
+        // The first line if lenient parsing skipped all rows
+        let idx = usize::min(rows.len(), height + 1).saturating_sub(1);
+        let line = section.text.lines().nth(idx).unwrap_or_default();
+        let at = if rows.len() > height { line } else { &line[line.len()..] };
+        return Err(ParseError::at(line, at, format!("{} rows per board", height)).line(section.first_line + idx));
+    }
+    Board::new(&rows.concat(), shape).map_err(|e| e.line(section.first_line))
 }
 
-fn parse_boards(s: &str, shape: (usize, usize)) -> Vec<Board>
+/// The input is the line of drawn numbers followed by the boards of `shape`
+/// (rows, cols), separated by empty lines
+fn parse_input(s: &str, shape: (usize, usize), context: &Context) -> Result<(Vec<u32>, Vec<Board>), ParseError>
 {
-    s
-        .split("\n\n") // Blocks
-        .map(|block| {
-            let data : Vec<u32> = block
-                .split(|c: char| c.is_whitespace())
-                .filter_map(|item| item.parse::<u32>().ok())
-                .collect();
-            Board::new(&data[..], shape)
-        }).collect()
+    let sections = parse::sections(s);
+    let (numbers, boards) = sections
+        .split_first()
+        .ok_or_else(|| ParseError::new(s, "a line of drawn numbers").line(1))?;
+    let drawn = numbers.items(context, parse_number)?;
+    if drawn.is_empty() {
+        return Err(ParseError::new(numbers.text, "at least one drawn number").line(numbers.first_line));
+    }
+    if boards.is_empty() {
+        return Err(ParseError::new("", "an empty line followed by the bingo boards").line(numbers.first_line + 1));
+    }
+    let boards = boards
+        .iter()
+        .map(|b| parse_board(b, shape, context))
+        .collect::<Result<Vec<Board>, ParseError>>()?;
+    Ok((drawn, boards))
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Field {
+pub enum Field {
     Checked(u32),
     Unchecked(u32),
 }
@@ -35,103 +85,100 @@ impl From<&u32> for Field {
     }
 }
 
-#[derive(Debug)]
-struct Board { 
-    data: Vec<Field>,
-    shape: (usize, usize),
+#[derive(Debug, Clone)]
+pub struct Board { 
+    grid: Grid<Field>,
     last_checked: u32,
 }
 
-#[derive(Debug)]
-struct Rows<'a> {
-    iter: std::slice::Chunks<'a, Field>,
-}
-
-#[derive(Debug)]
-struct Row<'a> {
-    data: std::slice::Iter<'a, Field>,
-}
-
-#[derive(Debug)]
-struct Cols<'a> {
-    data: &'a [Field],
-    shape: usize,
-    c: usize,
-}
-
-#[derive(Debug)]
-struct Col<'a> {
-    data: std::iter::StepBy<std::iter::Skip<std::slice::Iter<'a, Field>>>
-}
-
 trait Bingo {
     fn bingo(&mut self) -> bool;
 }
 
 impl<T> Bingo for T where T: Iterator<Item=Field> {
     fn bingo(self: &mut Self) -> bool {
-        self.all(|f| {
-            match f {
-                Field::Checked(_) => true,
-                _ => false,
-            }
-        })
+        self.all(|f| matches!(f, Field::Checked(_)))
     }
 }
 
-impl<'a> Iterator for Rows<'a> {
-    type Item = Row<'a>;
-    fn next(self: &mut Self) -> Option<Self::Item> {
-        Some(Row{data: self.iter.next()?.iter()})
-    }
+/// A set of cells that wins once all of them are checked
+#[derive(Debug, PartialEq, Clone)]
+pub enum WinPattern {
+    ROWS,           // Any full row
+    COLS,           // Any full column
+    DIAGONAL,       // From the top left corner, as far as the shorter side goes
+    ANTIDIAGONAL,   // The same from the top right corner
+    CORNERS,        // The four corners
+    BLACKOUT,       // The full card
+    MASK(Vec<Pos>), // Custom cells, a mask that is empty or leaves the board never wins
 }
 
-impl<'a> Iterator for Row<'a> {
-    type Item = Field;
-    fn next(self: &mut Self) -> Option<Self::Item> {
-        self.data.next().map(Field::to_owned)
-    }
-}
-
-impl<'a> Iterator for Cols<'a> {
-    type Item = Col<'a>;
-    fn next(self: &mut Self) -> Option<Self::Item> {
-        let col = Some(Col{
-            data: self.data.iter().skip(self.c).step_by(self.shape),
-        });
-        self.c += 1;
-        if self.c > self.shape { None }
-        else { col } 
-    }
+/// The puzzle's rules: a full row or column
+pub const STANDARD: &[WinPattern] = &[WinPattern::ROWS, WinPattern::COLS];
+
+/// The patterns selected by the bits of `WIN_PATTERNS`, lowest bit first
+const BUILTIN: &[WinPattern] = &[
+    WinPattern::ROWS, WinPattern::COLS, WinPattern::DIAGONAL, WinPattern::ANTIDIAGONAL, WinPattern::CORNERS, WinPattern::BLACKOUT,
+];
+
+/// The built-in patterns whose bits are set in `mask`
+pub fn selected_patterns(mask: usize) -> Vec<WinPattern> {
+    BUILTIN
+        .iter()
+        .enumerate()
+        .filter(|&(bit, _)| mask & 1 << bit != 0)
+        .map(|(_, pattern)| pattern.clone())
+        .collect()
 }
 
-impl<'a> Iterator for Col<'a> {
-    type Item = Field;
-    fn next(self: &mut Self) -> Option<Self::Item> {
-        self.data.next().map(Field::to_owned)
+impl WinPattern {
+    pub fn wins(self: &Self, board: &Board) -> bool {
+        use WinPattern::*;
+        let (rows, cols) = board.grid.shape();
+        let checked = |x: usize, y: usize| matches!(board.grid.get(Pos::new(x as isize, y as isize)), Some(Field::Checked(_)));
+        match self {
+            ROWS => board.rows().any(|mut row| row.bingo()),
+            COLS => board.cols().any(|mut col| col.bingo()),
+            DIAGONAL => (0..rows.min(cols)).all(|i| checked(i, i)),
+            ANTIDIAGONAL => (0..rows.min(cols)).all(|i| checked(cols - 1 - i, i)),
+            CORNERS => checked(0, 0) && checked(cols - 1, 0) && checked(0, rows - 1) && checked(cols - 1, rows - 1),
+            BLACKOUT => board.grid.values().copied().bingo(),
+            MASK(cells) => !cells.is_empty() && cells.iter().all(|&pos| {
+                matches!(board.grid.get(pos), Some(Field::Checked(_)))
+            }),
+        }
     }
 }
 
 impl Board {
-    fn new(data: &[u32], shape: (usize, usize)) -> Self {
-        Board{
-            data: data.iter().map(Field::from).collect(),
-            shape,
-            last_checked: 0,
+    /// A board of `shape` (rows, cols) from its numbers in row major order.
+    /// Fails if either side is 0, the win patterns need at least one cell,
+    /// or if there are not rows times cols numbers.
+    pub fn new(data: &[u32], shape: (usize, usize)) -> Result<Self, ParseError> {
+        let (rows, cols) = shape;
+        if rows == 0 || cols == 0 {
+            return Err(ParseError::new("", "a board of at least one row and column"));
         }
+        if data.len() != rows * cols {
+            return Err(ParseError::new("", format!("{} numbers for a board of {} by {}", rows * cols, rows, cols)));
+        }
+        Ok(Board{
+            grid: Grid::new(rows, cols, data.iter().map(Field::from).collect()),
+            last_checked: 0,
+        })
     }
 
-    fn rows(self: &Self) -> Rows {
-        Rows{ iter: self.data.chunks(self.shape.0) }
+    fn rows(self: &Self) -> impl Iterator<Item=impl Iterator<Item=Field> + '_> + '_ {
+        self.grid.rows().map(|row| row.iter().copied())
     }
 
-    fn cols(self: &Self) -> Cols {
-        Cols{ data: &self.data[..], shape: self.shape.1, c: 0 }
+    fn cols(self: &Self) -> impl Iterator<Item=impl Iterator<Item=Field> + '_> + '_ {
+        self.grid.cols().map(|col| col.copied())
     }
 
-    fn check(self: &mut Self, number: u32) {
+    pub fn check(self: &mut Self, number: u32) {
         use Field::*;
-        for f in self.data.iter_mut() {
+        for f in self.grid.values_mut() {
             if let &mut Unchecked(v) = f {
                 if v == number {
                     *f = Checked(number);
@@ -141,11 +188,11 @@ impl Board {
         }
     }
 
-    fn score(self: &mut Self) -> Option<u32> {
-        let row_bingo = self.rows().any(|mut row| row.bingo());
-        let col_bingo = self.cols().any(|mut col| col.bingo());
-        if row_bingo || col_bingo {
-            let score: u32 = self.data.iter().filter_map(|f| {
+    /// The sum of the unchecked numbers times the last checked one, once
+    /// any of the `patterns` wins
+    pub fn score(self: &Self, patterns: &[WinPattern]) -> Option<u32> {
+        if patterns.iter().any(|pattern| pattern.wins(self)) {
+            let score: u32 = self.grid.values().filter_map(|f| {
                 match f {
                     Field::Unchecked(v) => Some(v),
                     _ => None
@@ -157,31 +204,31 @@ impl Board {
     }
 }
 
-pub fn winning_bingo_score() -> u32
+/// The score of the first board to win, `None` if none does
+pub fn winning_score(mut boards: Vec<Board>, numbers: &[u32], patterns: &[WinPattern]) -> Option<u32>
 {
-    let numbers = parse_numbers(INPUT_NUMBERS);
-    let mut boards = parse_boards(INPUT_BOARDS, (BOARD_SIZE, BOARD_SIZE));
-    for num in numbers {
+    for &num in numbers {
         for board in boards.iter_mut() {
             board.check(num);
-            if let Some(score) = board.score() {
-                return score
+            if let Some(score) = board.score(patterns) {
+                return Some(score)
             }
         }
     }
-    return 0;
+    None
 }
 
-fn loosing_score<T>(mut boards: Vec<Board>, numbers: T) -> u32 
+/// The score of the last board to win, `None` if none does
+pub fn loosing_score<T>(mut boards: Vec<Board>, numbers: T, patterns: &[WinPattern]) -> Option<u32>
     where T: Iterator<Item=u32>
 {
-    let mut last_score: u32 = 0;
+    let mut last_score = None;
     for num in numbers {
         for board in boards.iter_mut() { board.check(num); } // Check number
-        boards = boards.into_iter().filter_map(|mut b| {
-            match b.score() {
+        boards = boards.into_iter().filter_map(|b| {
+            match b.score(patterns) {
                 Some(score) => {
-                    last_score = score; // Store last winning score
+                    last_score = Some(score); // Store last winning score
                     None // Filter out winning boards
                 },
                 None => Some(b)
@@ -191,10 +238,26 @@ fn loosing_score<T>(mut boards: Vec<Board>, numbers: T) -> u32
     last_score
 }
 
-pub fn loosing_bingo_score() -> u32 {
-    let numbers = parse_numbers(INPUT_NUMBERS);
-    let boards = parse_boards(INPUT_BOARDS, (BOARD_SIZE, BOARD_SIZE));
-    loosing_score(boards, numbers.into_iter())
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Parsed = (Vec<u32>, Vec<Board>);
+
+    fn parse(input: &str, params: &Values, context: &Context) -> Result<Self::Parsed, ParseError> {
+        parse_input(input, (BOARD_ROWS.get(params), BOARD_COLS.get(params)), context)
+    }
+
+    fn part1((numbers, boards): &Self::Parsed, params: &Values) -> Result<Answer, SolveError> {
+        winning_score(boards.clone(), numbers, &selected_patterns(WIN_PATTERNS.get(params)))
+            .map(Answer::from)
+            .ok_or_else(|| SolveError("No board wins".into()))
+    }
+
+    fn part2((numbers, boards): &Self::Parsed, params: &Values) -> Result<Answer, SolveError> {
+        loosing_score(boards.clone(), numbers.iter().copied(), &selected_patterns(WIN_PATTERNS.get(params)))
+            .map(Answer::from)
+            .ok_or_else(|| SolveError("No board wins".into()))
+    }
 }
 
 #[cfg(test)]
@@ -230,7 +293,7 @@ mod test {
     ];
 
     fn new_board() -> Board {
-        Board::new(&EXAMPLE_BOARDS[0][..], (5, 5))
+        Board::new(&EXAMPLE_BOARDS[0][..], (5, 5)).unwrap()
     }
 
     #[test]
@@ -259,8 +322,8 @@ mod test {
             ],
             first_row
         );
-        let last_row : Vec<Field> = board.rows().skip(4).next().unwrap().collect();
-        let last_value = last_row.into_iter().skip(4).next().unwrap();
+        let last_row : Vec<Field> = board.rows().nth(4).unwrap().collect();
+        let last_value = last_row.into_iter().nth(4).unwrap();
         assert_eq!(Unchecked(19), last_value);
     }
 
@@ -285,8 +348,8 @@ mod test {
             ],
             first_col
         );
-        let last_col : Vec<Field> = board.cols().skip(4).next().unwrap().collect();
-        let last_value = last_col.into_iter().skip(4).next().unwrap();
+        let last_col : Vec<Field> = board.cols().nth(4).unwrap().collect();
+        let last_value = last_col.into_iter().nth(4).unwrap();
         assert_eq!(Unchecked(19), last_value);
     }
 
@@ -303,36 +366,122 @@ mod test {
     fn row_is_bingo() {
         use Field::*;
         let data = [Unchecked(1u32), Unchecked(2), Unchecked(3)];
-        let mut row = Row{data: data[..].iter()};
-        assert!(!row.bingo());
+        assert!(!data.into_iter().bingo());
         let data = [Checked(1u32), Checked(2), Checked(3)];
-        let mut row = Row{data: data[..].iter()};
-        assert!(row.bingo());
+        assert!(data.into_iter().bingo());
+    }
+
+    #[test]
+    fn rectangular_board() {
+        let mut board = Board::new(&[1, 2, 3, 4, 5, 6], (2, 3)).unwrap();
+        assert_eq!(vec![vec![1, 2, 3], vec![4, 5, 6]], board.rows().map(|r| r.map(value).collect::<Vec<_>>()).collect::<Vec<_>>());
+        assert_eq!(vec![vec![1, 4], vec![2, 5], vec![3, 6]], board.cols().map(|c| c.map(value).collect::<Vec<_>>()).collect::<Vec<_>>());
+        board.check(3);
+        board.check(6);
+        assert_eq!(Some((1 + 2 + 4 + 5) * 6), board.score(STANDARD));
+    }
+
+    #[test]
+    fn win_patterns() {
+        use WinPattern::*;
+        // 3 rows of 4: 1 to 4, 5 to 8 and 9 to 12
+        let mut board = Board::new(&(1..=12).collect::<Vec<_>>(), (3, 4)).unwrap();
+        for n in [1, 6, 11] { board.check(n); }
+        assert!(DIAGONAL.wins(&board));
+        assert!(!ANTIDIAGONAL.wins(&board));
+        assert_eq!(None, board.score(STANDARD));
+        assert_eq!(Some((78 - 18) * 11), board.score(&[ROWS, DIAGONAL]));
+        for n in [4, 7, 10] { board.check(n); }
+        assert!(ANTIDIAGONAL.wins(&board));
+        assert!(!CORNERS.wins(&board));
+        board.check(9);
+        board.check(12);
+        assert!(CORNERS.wins(&board));
+        assert_eq!(Some((2 + 3 + 5 + 8) * 12), board.score(&[CORNERS]));
+        assert!(MASK(vec![Pos::new(0, 0), Pos::new(1, 1)]).wins(&board));
+        assert!(!MASK(vec![Pos::new(1, 0)]).wins(&board));
+        assert!(!MASK(vec![]).wins(&board));
+        assert!(!MASK(vec![Pos::new(4, 0)]).wins(&board));
+        assert!(!BLACKOUT.wins(&board));
+        for n in [2, 3, 5, 8] { board.check(n); }
+        assert!(BLACKOUT.wins(&board));
+
+        // A single cell is all the corners and both diagonals
+        let mut single = Board::new(&[7], (1, 1)).unwrap();
+        assert!(![DIAGONAL, ANTIDIAGONAL, CORNERS, BLACKOUT].iter().any(|p| p.wins(&single)));
+        single.check(7);
+        assert!([DIAGONAL, ANTIDIAGONAL, CORNERS, BLACKOUT].iter().all(|p| p.wins(&single)));
+    }
+
+    #[test]
+    fn invalid_board() {
+        let err = Board::new(&[], (0, 0)).unwrap_err();
+        assert_eq!("a board of at least one row and column", err.expected);
+        let err = Board::new(&[1, 2, 3], (2, 2)).unwrap_err();
+        assert_eq!("4 numbers for a board of 2 by 2", err.expected);
+    }
+
+    #[test]
+    fn select_win_patterns() {
+        use WinPattern::*;
+        assert_eq!(STANDARD, selected_patterns(WIN_PATTERNS.default).as_slice());
+        assert_eq!(vec![DIAGONAL, CORNERS], selected_patterns(4 + 16));
+        assert_eq!(BUILTIN, selected_patterns(63).as_slice());
+
+        let parsed = Puzzle::parse(EXAMPLE_INPUT, &Values::new(), &Context::default()).unwrap();
+        assert_eq!(Ok(Answer::from(4512)), Puzzle::part1(&parsed, &Values::new()));
+        let diagonal = Values::from([("win_patterns", 4)]);
+        assert_eq!(Ok(Answer::from(858)), Puzzle::part1(&parsed, &diagonal));
+        // Every number is drawn, but a full card scores 0
+        let blackout = Values::from([("win_patterns", 32)]);
+        assert_eq!(Ok(Answer::from(0)), Puzzle::part2(&parsed, &blackout));
+
+        let small = Values::from([("board_rows", 2), ("board_cols", 2)]);
+        let parsed = Puzzle::parse("1,4\n\n1 2\n3 4\n", &small, &Context::default()).unwrap();
+        assert_eq!(Ok(Answer::from((2 + 3) * 4)), Puzzle::part1(&parsed, &Values::from([("win_patterns", 4)])));
+        assert_eq!("No board wins", Puzzle::part1(&parsed, &small).unwrap_err().to_string());
+        assert_eq!("No board wins", Puzzle::part2(&parsed, &small).unwrap_err().to_string());
+    }
+
+    #[test]
+    fn scores_with_patterns() {
+        let boards = vec![Board::new(&[1, 2, 3, 4], (2, 2)).unwrap(), Board::new(&[4, 3, 2, 5], (2, 2)).unwrap()];
+        // The first column of the first board, then its diagonal
+        assert_eq!(Some(2 * 3 + 4 * 3), winning_score(boards.clone(), &[1, 3, 4], STANDARD));
+        assert_eq!(Some(2 * 4), winning_score(boards.clone(), &[1, 3, 4], &[WinPattern::DIAGONAL, WinPattern::ANTIDIAGONAL]));
+        assert_eq!(None, winning_score(boards.clone(), &[1, 3, 4], &[WinPattern::BLACKOUT]));
+        assert_eq!(None, loosing_score(boards.clone(), [1, 3, 4].into_iter(), &[WinPattern::BLACKOUT]));
+        // The first board wins at 1, the second one last at 5
+        assert_eq!(Some((3 + 2) * 5), loosing_score(boards, [4, 1, 5].into_iter(), &[WinPattern::DIAGONAL]));
+    }
+
+    fn value(field: Field) -> u32 {
+        match field { Field::Checked(v) | Field::Unchecked(v) => v }
     }
 
     #[test]
     fn bingo_row_example() {
-        let mut board = Board::new(&EXAMPLE_BOARDS[2][..], (BOARD_SIZE, BOARD_SIZE));
+        let mut board = Board::new(&EXAMPLE_BOARDS[2][..], (BOARD_SIZE, BOARD_SIZE)).unwrap();
         for n in EXAMPLE_NUMBERS {
             board.check(*n);
-            if let Some(_) = board.score() { 
+            if board.score(STANDARD).is_some() { 
                 break; 
             }
         }
-        assert_eq!(Some(4512), board.score());
+        assert_eq!(Some(4512), board.score(STANDARD));
     }
 
     #[test]
     fn bingo_example() {
-        let mut boards = Vec::<Board>::new();
-        for board_data in EXAMPLE_BOARDS {
-            boards.push(Board::new(board_data, (BOARD_SIZE, BOARD_SIZE)));
-        }
+        let mut boards: Vec<Board> = EXAMPLE_BOARDS
+            .iter()
+            .map(|board_data| Board::new(board_data, (BOARD_SIZE, BOARD_SIZE)).unwrap())
+            .collect();
 
         for num in EXAMPLE_NUMBERS {
             for board in boards.iter_mut() {
                 board.check(*num);
-                if let Some(score) = board.score() {
+                if let Some(score) = board.score(STANDARD) {
                     assert_eq!(4512, score);
                     return;
                 }
@@ -341,14 +490,51 @@ mod test {
         panic!("No board won");
     }
 
+    const EXAMPLE_INPUT: &str = include_str!("../data/4/examples/example.in");
+
+    #[test]
+    fn parse_example_input() {
+        let (numbers, boards) = parse_input(EXAMPLE_INPUT, (BOARD_SIZE, BOARD_SIZE), &Context::default()).unwrap();
+        assert_eq!(27, numbers.len());
+        assert_eq!(3, boards.len());
+        assert_eq!(Some(Field::Unchecked(14)), boards[1].rows().nth(4).unwrap().next());
+    }
+
+    #[test]
+    fn parse_input_errors() {
+        let err = parse_input(&EXAMPLE_INPUT.replace("26,1", "26,x"), (BOARD_SIZE, BOARD_SIZE), &Context::default()).unwrap_err();
+        assert_eq!((Some(1), 69), (err.line, err.column));
+        let err = parse_input(&EXAMPLE_INPUT.replace("16  7", "16"), (BOARD_SIZE, BOARD_SIZE), &Context::default()).unwrap_err();
+        assert_eq!((Some(5), 11), (err.line, err.column));
+        let err = parse_input(&EXAMPLE_INPUT.replace("14 21 16 12  6\n", ""), (BOARD_SIZE, BOARD_SIZE), &Context::default()).unwrap_err();
+        assert_eq!((Some(12), 14), (err.line, err.column));
+        let err = parse_input(&EXAMPLE_INPUT.replace("\n\n 3 15", "\n 3 15"), (BOARD_SIZE, BOARD_SIZE), &Context::default()).unwrap_err();
+        assert_eq!((Some(8), 0), (err.line, err.column));
+        assert!(parse_input("1,2,3\n", (BOARD_SIZE, BOARD_SIZE), &Context::default()).is_err());
+    }
+
+    #[test]
+    fn parse_leniently() {
+        let lenient = Context::new(parse::Mode::Lenient);
+        let (numbers, _) = parse_input(&EXAMPLE_INPUT.replacen("7,4,", "7,x,4,", 1), (BOARD_SIZE, BOARD_SIZE), &lenient).unwrap();
+        assert_eq!(&numbers[..], EXAMPLE_NUMBERS);
+        assert_eq!(1, lenient.into_skipped().len());
+
+        // All rows of the board skipped
+        let lenient = Context::new(parse::Mode::Lenient);
+        let err = parse_input("1,2,3\n\nx y z a b\n", (BOARD_SIZE, BOARD_SIZE), &lenient).unwrap_err();
+        assert_eq!((Some(3), "5 rows per board"), (err.line, err.expected.as_str()));
+        assert!(parse_input("x\n\n1 2\n3 4\n", (2, 2), &Context::new(parse::Mode::Lenient)).is_err());
+    }
+
     #[test]
     fn last_win_example() {
-        let mut boards = Vec::<Board>::new();
-        for board_data in EXAMPLE_BOARDS {
-            boards.push(Board::new(board_data, (BOARD_SIZE, BOARD_SIZE)));
-        }
-        let loosing_score = loosing_score(boards, EXAMPLE_NUMBERS[..].iter().copied());
-        assert_eq!(1924, loosing_score);
+        let boards: Vec<Board> = EXAMPLE_BOARDS
+            .iter()
+            .map(|board_data| Board::new(board_data, (BOARD_SIZE, BOARD_SIZE)).unwrap())
+            .collect();
+        let loosing_score = loosing_score(boards, EXAMPLE_NUMBERS[..].iter().copied(), STANDARD);
+        assert_eq!(Some(1924), loosing_score);
     }
 
 }
@@ -1,110 +1,52 @@
 use std::str::FromStr;
-use std::iter::FromIterator;
 use std::collections::HashMap;
-use std::boxed::Box;
 
-const INPUT: &str = include_str!("../data/5/input");
+use crate::params::Values;
+use crate::solution::{Answer, Context, ParseError, Solution, SolveError};
+use crate::vec2::Vec2;
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
-struct Point(u64, u64);
+pub type Point = Vec2<i64>;
 
 #[derive(Debug, PartialEq, Eq)]
-struct Line (Point, Point);
+pub struct Line (Point, Point);
 
-#[derive(Debug, PartialEq)]
-struct ParsePointError {}
-impl FromStr for Point {
-    type Err = ParsePointError;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let coords: Vec::<u64> = s
-            .split(",")
-            .map(str::trim)
-            .filter_map(|p| p.parse::<u64>().ok())
-            .collect();
-        if let [x, y] = coords[..]{
-            Ok(Point(x, y))
-        }
-        else {
-            Err(ParsePointError{})
-        }
-    }
-}
-
-#[derive(Debug)]
-struct ParseLineError {}
 impl FromStr for Line {
-    type Err = ParseLineError;
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let points : Vec<Point> = s
-            .split("->")
-            .map(str::trim)
-            .filter_map(|p| p.parse::<Point>().ok())
-            .collect();
-        if let [from, to] = points[..]{
-            Ok(Line(from, to))
-        }
-        else {
-            Err(ParseLineError{})
+        let mut points = s.split("->").map(str::trim);
+        match (points.next(), points.next(), points.next()) {
+            (Some(from), Some(to), None) => {
+                let from = from.parse::<Point>().map_err(|e| e.within(s, from))?;
+                let to = to.parse::<Point>().map_err(|e| e.within(s, to))?;
+                Ok(Line(from, to))
+            }
+            (_, _, Some(extra)) => Err(ParseError::at(s, extra, "end of line")),
+            _ => Err(ParseError::at(s, &s[s.len()..], "a line x1,y1 -> x2,y2")),
         }
     }
 }
 
 impl Line {
+    /// All points from start to end, one step apart
     fn fill(self: &Self) -> Vec<Point> {
-        if self.is_horizontal() {
-            (self.start_x()..=self.end_x()).map(|i| Point(i, self.0.1)).collect()
-        }
-        else if self.is_vertical() {
-            (self.start_y()..=self.end_y()).map(|i| Point(self.0.0, i)).collect()
-        }
-        else if self.is_diagonal() {
-            let it_x = self.x_range();
-            let it_y = self.y_range();
-            it_x.into_iter().zip(it_y.into_iter()).map(|(x, y)| Point(x, y)).collect()
-        }
-        else {
+        if !self.is_straight() && !self.is_diagonal() {
             panic!("Non fillable line");
         }
+        let step = (self.1 - self.0).signum();
+        (0..=self.0.chebyshev(self.1)).map(|i| self.0 + step * i).collect()
     }
 
-    fn x_range(self: &Self) -> Box<dyn Iterator<Item=u64>> {
-        let it_x = self.start_x()..=self.end_x();
-        if self.x_descending() {
-            Box::new(it_x.into_iter().rev())
-        }
-        else {
-            Box::new(it_x.into_iter())
-        }
-    }
-
-    fn y_range(self: &Self) -> Box<dyn Iterator<Item=u64>> {
-        let it_y = self.start_y()..=self.end_y();
-        if self.y_descending() {
-            Box::new(it_y.into_iter().rev())
-        }
-        else {
-            Box::new(it_y.into_iter())
-        }
-    }
-
-    fn start_x(self: &Self) -> u64 { u64::min(self.0.0, self.1.0) }
-    fn end_x(self: &Self)   -> u64 { u64::max(self.0.0, self.1.0) }
-    fn start_y(self: &Self) -> u64 { u64::min(self.0.1, self.1.1) }
-    fn end_y(self: &Self)   -> u64 { u64::max(self.0.1, self.1.1) }
-
-    fn x_descending(self: &Self) -> bool { self.0.0 > self.1.0 }
-    fn y_descending(self: &Self) -> bool { self.0.1 > self.1.1 }
-
     fn is_horizontal(self: &Self) -> bool {
-        self.start_y() == self.end_y()
+        self.0.y == self.1.y
     }
 
     fn is_vertical(self: &Self) -> bool {
-        self.start_x() == self.end_x()
+        self.0.x == self.1.x
     }
 
     fn is_diagonal(self: &Self) -> bool {
-        self.end_x()-self.start_x() == self.end_y()-self.start_y()
+        let d = self.1 - self.0;
+        d.x.abs() == d.y.abs()
     }
 
     fn is_straight(self: &Self) -> bool {
@@ -123,10 +65,7 @@ impl FromIterator<Point> for PointCount {
             data: HashMap::<Point, u64>::new(),
         };
         for point in it {
-            match pc.data.get_mut(&point) {
-                Some(count) => {*count+=1;}
-                None => {pc.data.insert(point, 1);}
-            }
+            *pc.data.entry(point).or_insert(0) += 1;
         }
         pc
     }
@@ -134,34 +73,49 @@ impl FromIterator<Point> for PointCount {
 
 impl PointCount {
     fn overlaps(self: &Self) -> u64 {
-        self.data.iter().filter(|(_, c)| **c>1).count() as u64
+        self.data.values().filter(|c| **c>1).count() as u64
     }
 }
 
-fn parse_input(s: &str) -> Vec<Line> {
-    s
-        .split('\n')
-        .filter_map(|item| item.parse::<Line>().ok())
-        .collect()
+fn parse_input(s: &str, context: &Context) -> Result<Vec<Line>, ParseError> {
+    context.lines(s, Line::from_str)
 }
 
-pub fn overlapping_straight_line_count() -> u64 {
-    parse_input(INPUT)
-        .into_iter()
-        .filter(Line::is_straight)
-        .flat_map(|l| l.fill().into_iter())
+fn overlapping_straight_line_count(lines: &[Line]) -> u64 {
+    lines
+        .iter()
+        .filter(|l| l.is_straight())
+        .flat_map(|l| l.fill())
         .collect::<PointCount>()
         .overlaps()
 }
 
-pub fn overlapping_diagonal_line_count() -> u64 {
-    parse_input(INPUT)
-        .into_iter()
-        .flat_map(|l| l.fill().into_iter())
+fn overlapping_diagonal_line_count(lines: &[Line]) -> u64 {
+    lines
+        .iter()
+        .flat_map(|l| l.fill())
         .collect::<PointCount>()
         .overlaps()
 }
 
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Parsed = Vec<Line>;
+
+    fn parse(input: &str, _params: &Values, context: &Context) -> Result<Self::Parsed, ParseError> {
+        parse_input(input, context)
+    }
+
+    fn part1(lines: &Self::Parsed, _params: &Values) -> Result<Answer, SolveError> {
+        Ok(overlapping_straight_line_count(lines).into())
+    }
+
+    fn part2(lines: &Self::Parsed, _params: &Values) -> Result<Answer, SolveError> {
+        Ok(overlapping_diagonal_line_count(lines).into())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -169,7 +123,7 @@ mod test {
     #[test]
     fn parse_point() {
         let point = "1,2".parse::<Point>().unwrap();
-        assert_eq!(Point(1,2), point);
+        assert_eq!(Point::new(1, 2), point);
         assert!("".parse::<Point>().is_err());
         assert!(",".parse::<Point>().is_err());
         assert!("1,".parse::<Point>().is_err());
@@ -179,14 +133,24 @@ mod test {
     #[test]
     fn parse_line() {
         let line = "1,2 -> 3,4".parse::<Line>().unwrap();
-        assert_eq!(Line(Point(1,2),Point(3,4)), line);
+        assert_eq!(Line(Point::new(1, 2),Point::new(3, 4)), line);
         let line = " 1, 2 -> 3 ,4 ".parse::<Line>().unwrap();
-        assert_eq!(Line(Point(1,2),Point(3,4)), line);
+        assert_eq!(Line(Point::new(1, 2),Point::new(3, 4)), line);
         assert!("".parse::<Line>().is_err());
         assert!("1 -> 2".parse::<Line>().is_err());
         assert!("1,2 -> 3".parse::<Line>().is_err());
     }
 
+    #[test]
+    fn parse_error_location() {
+        let err = "1,2 -> 3,x".parse::<Line>().unwrap_err();
+        assert_eq!(9, err.column);
+        let err = "1,2 -> 3".parse::<Line>().unwrap_err();
+        assert_eq!(8, err.column);
+        let err = parse_input("0,9 -> 5,9\n  8,0 => 0,8", &Context::default()).unwrap_err();
+        assert_eq!((Some(2), 12), (err.line, err.column));
+    }
+
     #[test]
     fn line_is_horizontal() {
         let line: Line = "1,2 -> 4,2".parse().unwrap();
@@ -224,17 +188,17 @@ mod test {
         let line: Line = "0,1 -> 3,1".parse().unwrap();
         let points = line.fill();
         assert_eq!(
-            vec![Point(0,1), Point(1,1), Point(2,1), Point(3,1)],
+            vec![Point::new(0, 1), Point::new(1, 1), Point::new(2, 1), Point::new(3, 1)],
             points
         );
     }
 
     #[test]
     fn fill_horizontal_line_reversed() {
-        assert_eq!(4, Line(Point(0, 0), Point(0, 3)).fill().iter().count());
-        assert_eq!(4, Line(Point(0, 3), Point(0, 0)).fill().iter().count());
-        assert_eq!(4, Line(Point(0, 0), Point(3, 0)).fill().iter().count());
-        assert_eq!(4, Line(Point(3, 0), Point(0, 0)).fill().iter().count());
+        assert_eq!(4, Line(Point::new(0, 0), Point::new(0, 3)).fill().len());
+        assert_eq!(4, Line(Point::new(0, 3), Point::new(0, 0)).fill().len());
+        assert_eq!(4, Line(Point::new(0, 0), Point::new(3, 0)).fill().len());
+        assert_eq!(4, Line(Point::new(3, 0), Point::new(0, 0)).fill().len());
     }
 
     #[test]
@@ -242,68 +206,58 @@ mod test {
         let line: Line = "1,0 -> 1,3".parse().unwrap();
         let points = line.fill();
         assert_eq!(
-            vec![Point(1,0), Point(1,1), Point(1,2), Point(1,3)],
+            vec![Point::new(1, 0), Point::new(1, 1), Point::new(1, 2), Point::new(1, 3)],
             points
         );
     }
 
     #[test]
     fn fill_diagnoal() {
-        assert_eq!(4, Line(Point(0, 0), Point(3, 3)).fill().iter().count());
-        assert_eq!(4, Line(Point(3, 3), Point(0, 0)).fill().iter().count());
-        assert_eq!(4, Line(Point(0, 3), Point(3, 0)).fill().iter().count());
-        assert_eq!(4, Line(Point(3, 0), Point(0, 3)).fill().iter().count());
+        assert_eq!(4, Line(Point::new(0, 0), Point::new(3, 3)).fill().len());
+        assert_eq!(4, Line(Point::new(3, 3), Point::new(0, 0)).fill().len());
+        assert_eq!(4, Line(Point::new(0, 3), Point::new(3, 0)).fill().len());
+        assert_eq!(4, Line(Point::new(3, 0), Point::new(0, 3)).fill().len());
 
         assert_eq!(
-            vec![Point(0,3),Point(1,2),Point(2,1),Point(3,0)],
-            Line(Point(0,3), Point(3, 0)).fill()
+            vec![Point::new(0, 3),Point::new(1, 2),Point::new(2, 1),Point::new(3, 0)],
+            Line(Point::new(0, 3), Point::new(3, 0)).fill()
         );
 
         assert_eq!(
-            vec![Point(2,2),Point(1,1),Point(0,0)],
-            Line(Point(2,2), Point(0, 0)).fill()
+            vec![Point::new(2, 2),Point::new(1, 1),Point::new(0, 0)],
+            Line(Point::new(2, 2), Point::new(0, 0)).fill()
         );
     }
 
     #[test]
     fn count_points() {
-        let points1 = vec![Point(0,1), Point(1,1), Point(2,1), Point(3,1)];
-        let points2 = vec![Point(1,0), Point(1,1), Point(1,2), Point(1,3)];
-        let count: PointCount = points1.into_iter().chain(points2.into_iter()).collect();
+        let points1 = vec![Point::new(0, 1), Point::new(1, 1), Point::new(2, 1), Point::new(3, 1)];
+        let points2 = vec![Point::new(1, 0), Point::new(1, 1), Point::new(1, 2), Point::new(1, 3)];
+        let count: PointCount = points1.into_iter().chain(points2).collect();
         let overlaps = count.overlaps();
         assert_eq!(1, overlaps);
     }
 
-    const EXAMPLE_LINES: &str = "
-        0,9 -> 5,9
-        8,0 -> 0,8
-        9,4 -> 3,4
-        2,2 -> 2,1
-        7,0 -> 7,4
-        6,4 -> 2,0
-        0,9 -> 2,9
-        3,4 -> 1,4
-        0,0 -> 8,8
-        5,5 -> 8,2";
+    const EXAMPLE_LINES: &str = include_str!("../data/5/examples/example.in");
 
     #[test]
     fn example_overlaps_straight_only() {
-        let lines: Vec::<Line> = parse_input(EXAMPLE_LINES);
-        assert_eq!(10, lines.iter().count());
+        let lines: Vec::<Line> = parse_input(EXAMPLE_LINES, &Context::default()).unwrap();
+        assert_eq!(10, lines.len());
         let straight_lines: Vec::<Line> = lines.into_iter().filter(Line::is_straight).collect();
-        assert_eq!(6, straight_lines.iter().count());
-        let points: Vec::<Point> = straight_lines.into_iter().flat_map(|l| l.fill().into_iter()).collect();
-        assert_eq!(26, points.iter().count());
+        assert_eq!(6, straight_lines.len());
+        let points: Vec::<Point> = straight_lines.into_iter().flat_map(|l| l.fill()).collect();
+        assert_eq!(26, points.len());
         let counter: PointCount = points.into_iter().collect();
         assert_eq!(5, counter.overlaps());
     }
 
     #[test]
     fn example_overlaps_diagnoal() {
-        let lines: Vec::<Line> = parse_input(EXAMPLE_LINES);
-        assert_eq!(10, lines.iter().count());
-        let points: Vec::<Point> = lines.into_iter().flat_map(|l| l.fill().into_iter()).collect();
-        assert_eq!(53, points.iter().count());
+        let lines: Vec::<Line> = parse_input(EXAMPLE_LINES, &Context::default()).unwrap();
+        assert_eq!(10, lines.len());
+        let points: Vec::<Point> = lines.into_iter().flat_map(|l| l.fill()).collect();
+        assert_eq!(53, points.len());
         let counter: PointCount = points.into_iter().collect();
         assert_eq!(12, counter.overlaps());
     }
@@ -318,22 +272,22 @@ mod test {
             2,0->0,2
         ";
 
-        let lines: Vec::<Line> = parse_input(CUSTOM_LINES);
-        assert_eq!(5, lines.iter().count());
-        let points: Vec::<Point> = lines.into_iter().flat_map(|l| l.fill().into_iter()).collect();
-        assert_eq!(15, points.iter().count());
+        let lines: Vec::<Line> = parse_input(CUSTOM_LINES, &Context::default()).unwrap();
+        assert_eq!(5, lines.len());
+        let points: Vec::<Point> = lines.into_iter().flat_map(|l| l.fill()).collect();
+        assert_eq!(15, points.len());
         let counter: PointCount = points.into_iter().collect();
         assert_eq!(4, counter.overlaps());
     }
 
     #[test]
     fn pointlike() {
-        assert_eq!(1, (0..=0).into_iter().rev().count());
+        assert_eq!(1, (0..=0).rev().count());
     }
 
     fn expect_overlaps(data: &str, expectation: u64) {
-        let lines: Vec<Line> = parse_input(data).into_iter().collect();
-        let points: Vec<Point> = lines.into_iter().flat_map(|l| l.fill().into_iter()).collect();
+        let lines: Vec<Line> = parse_input(data, &Context::default()).unwrap();
+        let points: Vec<Point> = lines.into_iter().flat_map(|l| l.fill()).collect();
         let counter: PointCount = points.into_iter().collect();
         assert_eq!(expectation, counter.overlaps());
     }
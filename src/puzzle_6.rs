@@ -1,17 +1,56 @@
-const INPUT: &str = include_str!("../data/6/input");
+use crate::params::{Param, Values};
+use crate::parse;
+use crate::solution::{Answer, Context, ParseError, Solution, SolveError};
+
+pub const CYCLE: Param = Param{
+    name: "cycle", description: "days between the births of a fish", default: 7, range: 1..=100,
+};
+
+pub const NEWBORN: Param = Param{
+    name: "newborn", description: "timer of a newborn fish", default: 8, range: 0..=100,
+};
+
+pub const PART1_DAYS: Param = Param{
+    name: "part1_days", description: "days simulated in part 1", default: 80, range: 0..=400,
+};
+
+pub const PART2_DAYS: Param = Param{
+    name: "part2_days", description: "days simulated in part 2", default: 256, range: 0..=400,
+};
+
+pub const PARAMS: &[Param] = &[CYCLE, NEWBORN, PART1_DAYS, PART2_DAYS];
 
 #[derive(Debug, PartialEq, Clone)]
-struct Fish(u8);
+pub struct Fish(u8);
+
+/// The timers of the fish, from the `cycle` and `newborn` parameters
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Lifecycle {
+    pub cycle: u8,      // Days between births
+    pub newborn: u8,    // Timer of a newborn fish
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Lifecycle{ cycle: CYCLE.default as u8, newborn: NEWBORN.default as u8 }
+    }
+}
+
+impl From<&Values> for Lifecycle {
+    fn from(params: &Values) -> Self {
+        Lifecycle{ cycle: CYCLE.get(params) as u8, newborn: NEWBORN.get(params) as u8 }
+    }
+}
 
 impl Fish {
-    fn new() -> Self {
-        Fish(8)
+    fn new(life: Lifecycle) -> Self {
+        Fish(life.newborn)
     }
 
-    fn grow(self: &mut Self) -> Option<Fish> {
+    fn grow(self: &mut Self, life: Lifecycle) -> Option<Fish> {
         if self.0 == 0 {
-            self.0 = 6;
-            Some(Fish::new())
+            self.0 = life.cycle - 1;
+            Some(Fish::new(life))
         }
         else {
             self.0 -= 1;
@@ -19,24 +58,24 @@ impl Fish {
         }
     }
 
-    fn birthdays(self: &Self, start_day: usize) -> Birthdays {
+    fn birthdays(self: &Self, start_day: usize, life: Lifecycle) -> Birthdays {
         Birthdays{
             next: self.0 as usize + start_day,
+            cycle: life.cycle as usize,
         }
     }
 }
 
-const GENERATION_TIME: usize = 7;
-
 struct Birthdays{
     next: usize,
+    cycle: usize,
 }
 
 impl Iterator for Birthdays {
     type Item=usize;
     fn next(self: &mut Self) -> Option<Self::Item> {
         let ret = Some(self.next+1);
-        self.next += GENERATION_TIME;
+        self.next += self.cycle;
         ret
     }
 }
@@ -48,107 +87,169 @@ impl From<u8> for Fish {
 }
 
 #[allow(unused)]
-fn simulate_growth(mut fish: Vec::<Fish>, days: usize) -> usize {
+fn simulate_growth(mut fish: Vec::<Fish>, days: usize, life: Lifecycle) -> usize {
     for _ in 0..days {
-        let new_fish: Vec<Fish> = fish.iter_mut().filter_map(|f| f.grow()).collect();
+        let new_fish: Vec<Fish> = fish.iter_mut().filter_map(|f| f.grow(life)).collect();
         fish.extend(new_fish);
     }
     fish.len()
 }
 
-fn calculate_growth(fish: Vec::<Fish>, days: usize) -> usize {
-    let mut births = vec![0; days+1];
+/// The number of fish after `days`, `None` if it does not fit a `usize`
+fn calculate_growth(fish: &[Fish], days: usize, life: Lifecycle) -> Option<usize> {
+    let mut births = vec![0usize; days+1];
     // Add the fish birth for the initial fish population
     for f in fish.iter() {
-        for bday in f.birthdays(0).take_while(|i| *i<=days) {
+        for bday in f.birthdays(0, life).take_while(|i| *i<=days) {
             births[bday] += 1;
         }
     }
     // Add the fish birth for all the offspring of the initial population day by day
     for d in 0..days {
-        let fish = Fish::new();
-        for bday in fish.birthdays(d).take_while(|i| *i<=days) {
-            births[bday] += births[d];
+        let fish = Fish::new(life);
+        for bday in fish.birthdays(d, life).take_while(|i| *i<=days) {
+            births[bday] = births[bday].checked_add(births[d])?;
         }
     }
-    births.iter().sum::<usize>() + fish.len() // Final fish count is sum of all offspring + initial fish
+    // Final fish count is sum of all offspring + initial fish
+    births.iter().try_fold(fish.len(), |count, &b| count.checked_add(b))
+}
+
+fn count_fish(fish: &[Fish], days: usize, life: Lifecycle) -> Result<Answer, SolveError> {
+    calculate_growth(fish, days, life)
+        .map(Answer::from)
+        .ok_or_else(|| SolveError(format!("Too many lanternfish to count after {} days", days)))
 }
 
-fn parse_input(s: &str) -> Vec<Fish> {
-    s
-        .split(',')
-        .map(str::trim)
-        .map(str::parse::<u8>)
-        .map(Result::unwrap)
-        .map(Fish::from)
-        .collect()
+fn parse_fish(f: &str) -> Result<Fish, ParseError> {
+    parse::token(f, f, "a fish timer").map(Fish)
 }
 
-pub fn lanternfish_population(days: usize) -> usize {
-    let fish = parse_input(INPUT);
-    calculate_growth(fish, days)
+/// The comma separated timers of the fish, at least one of them
+fn parse_input(s: &str, context: &Context) -> Result<Vec<Fish>, ParseError> {
+    let fish = context.items(s, parse_fish)?;
+    if fish.is_empty() {
+        return Err(ParseError::new(s.trim(), "at least one fish timer").line(1));
+    }
+    Ok(fish)
+}
+
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Parsed = Vec<Fish>;
+
+    fn parse(input: &str, _params: &Values, context: &Context) -> Result<Self::Parsed, ParseError> {
+        parse_input(input, context)
+    }
+
+    fn part1(fish: &Self::Parsed, params: &Values) -> Result<Answer, SolveError> {
+        count_fish(fish, PART1_DAYS.get(params), Lifecycle::from(params))
+    }
+
+    fn part2(fish: &Self::Parsed, params: &Values) -> Result<Answer, SolveError> {
+        count_fish(fish, PART2_DAYS.get(params), Lifecycle::from(params))
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    const LIFE: Lifecycle = Lifecycle{ cycle: 7, newborn: 8 };
+
+    #[test]
+    fn default_lifecycle() {
+        assert_eq!(LIFE, Lifecycle::default());
+        assert_eq!(LIFE, Lifecycle::from(&Values::new()));
+    }
+
     #[test]
     fn grow_fish() {
-        let mut f = Fish::new();
+        let mut f = Fish::new(LIFE);
         assert_eq!(8, f.0);
         for _ in 0..8 {
-            assert_eq!(None, f.grow());
+            assert_eq!(None, f.grow(LIFE));
         }
-        assert_eq!(Some(Fish(8)), f.grow());
+        assert_eq!(Some(Fish(8)), f.grow(LIFE));
     }
 
     fn example_fish() -> Vec::<Fish> {
-        vec![3u8,4,3,1,2].into_iter().map(Fish::from).collect()
+        [3u8,4,3,1,2].into_iter().map(Fish::from).collect()
     }
 
     #[test]
     fn lanternfish_example_18() {
         let fish = example_fish();
-        assert_eq!(26, simulate_growth(fish, 18));
+        assert_eq!(26, simulate_growth(fish, 18, LIFE));
     }
 
     #[test]
     fn lanternfish_example_80() {
         let fish = example_fish();
-        assert_eq!(5934, simulate_growth(fish, 80));
+        assert_eq!(5934, simulate_growth(fish, 80, LIFE));
     }
 
     #[test]
     fn birthdays_from_0() {
-        let fish = Fish::new();
-        let birthdays: Vec::<usize> = fish.birthdays(0).take_while(|i| *i<32).collect();
+        let fish = Fish::new(LIFE);
+        let birthdays: Vec::<usize> = fish.birthdays(0, LIFE).take_while(|i| *i<32).collect();
         assert_eq!(vec![9, 16, 23, 30], birthdays);
     }
 
     #[test]
     fn birthdays() {
         let fish = Fish(2);
-        let birthdays: Vec::<usize> = fish.birthdays(25).take_while(|i| *i<50).collect();
+        let birthdays: Vec::<usize> = fish.birthdays(25, LIFE).take_while(|i| *i<50).collect();
         assert_eq!(vec![28, 35, 42, 49], birthdays);
     }
 
+    #[test]
+    fn parse_fish_input() {
+        let strict = Context::default();
+        assert_eq!(example_fish(), parse_input("3,4,3,1,2\n", &strict).unwrap());
+        let err = parse_input("3,4,-3,1", &strict).unwrap_err();
+        assert_eq!((Some(1), 4), (err.line, err.column));
+        assert!(parse_input("", &strict).is_err());
+
+        let lenient = Context::new(parse::Mode::Lenient);
+        assert_eq!(example_fish(), parse_input("3,4,x,3,1,2\n", &lenient).unwrap());
+        assert_eq!(vec![(Some(1), 4)], lenient.into_skipped().iter().map(|e| (e.line, e.column)).collect::<Vec<_>>());
+    }
+
     #[test]
     fn calculate_fish_growth_simple() {
         let fish = vec![Fish(2), Fish(4)];
-        assert_eq!(5, calculate_growth(fish.clone(), 10));
-        assert_eq!(8, calculate_growth(fish, 15));
+        assert_eq!(Some(5), calculate_growth(&fish, 10, LIFE));
+        assert_eq!(Some(8), calculate_growth(&fish, 15, LIFE));
+    }
+
+    #[test]
+    fn growth_with_params() {
+        let life = Lifecycle::from(&Values::from([("cycle", 5), ("newborn", 6)]));
+        assert_eq!(Lifecycle{ cycle: 5, newborn: 6 }, life);
+        let calculated = calculate_growth(&example_fish(), 40, life);
+        assert_eq!(Some(simulate_growth(example_fish(), 40, life)), calculated);
+        assert!(calculated > calculate_growth(&example_fish(), 40, LIFE));
     }
 
     #[test]
     fn calculate_fish_growth_80() {
         let fish = example_fish();
-        assert_eq!(5934, calculate_growth(fish, 80));
+        assert_eq!(Some(5934), calculate_growth(&fish, 80, LIFE));
     }
 
     #[test]
     fn calculate_fish_growth_256() {
         let fish = example_fish();
-        assert_eq!(26984457539, calculate_growth(fish, 256));
+        assert_eq!(Some(26984457539), calculate_growth(&fish, 256, LIFE));
+    }
+
+    #[test]
+    fn growth_overflow() {
+        let values = Values::from([("cycle", 1), ("newborn", 0)]);
+        assert_eq!(None, calculate_growth(&example_fish(), 256, Lifecycle::from(&values)));
+        let err = Puzzle::part2(&example_fish(), &values).unwrap_err();
+        assert_eq!("Too many lanternfish to count after 256 days", err.to_string());
     }
 }
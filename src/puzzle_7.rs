@@ -1,13 +1,20 @@
-const INPUT: &str = include_str!("../data/7/input");
+use crate::params::Values;
+use crate::parse;
+use crate::solution::{Answer, Context, ParseError, Solution, SolveError};
 
 type CostFn = fn(i32, i32) -> u32;
 
-fn parse_input(s: &str) -> Vec<i32> { s
-        .split(',')
-        .map(str::trim)
-        .map(str::parse::<i32>)
-        .map(Result::unwrap)
-        .collect()
+fn parse_crab(c: &str) -> Result<i32, ParseError> {
+    parse::token(c, c, "a crab position")
+}
+
+/// The comma separated crab positions, at least one of them
+fn parse_input(s: &str, context: &Context) -> Result<Vec<i32>, ParseError> {
+    let crabs = context.items(s, parse_crab)?;
+    if crabs.is_empty() {
+        return Err(ParseError::new(s.trim(), "at least one crab position").line(1));
+    }
+    Ok(crabs)
 }
 
 fn constant_cost(from: i32, to: i32) -> u32 {
@@ -19,29 +26,43 @@ fn linear_cost(from: i32, to: i32) -> u32 {
     ((i32::pow(steps, 2)+steps)/2) as u32
 }
 
-fn align_cost(coords: &Vec<i32>, coord: i32, cost_fn: CostFn) -> u32 {
+fn align_cost(coords: &[i32], coord: i32, cost_fn: CostFn) -> u32 {
     coords
         .iter()
         .map(|&c| cost_fn(c, coord))
         .sum::<u32>()
 }
 
-fn best_align_cost(coords: Vec<i32>, cost_fn: CostFn) -> Option<u32> {
+fn best_align_cost(coords: &[i32], cost_fn: CostFn) -> Option<u32> {
     let min = *coords.iter().min()?;
     let max = *coords.iter().max()?;
-    (min..=max).into_iter()
-        .map(|c| align_cost(&coords, c, cost_fn))
+    (min..=max)
+        .map(|c| align_cost(coords, c, cost_fn))
         .min()
 }
 
-pub fn align_crabs_cost() -> u32 {
-    let crabs = parse_input(INPUT);
-    best_align_cost(crabs, constant_cost).unwrap()
+fn align(crabs: &[i32], cost_fn: CostFn) -> Result<Answer, SolveError> {
+    best_align_cost(crabs, cost_fn)
+        .map(Answer::from)
+        .ok_or_else(|| SolveError("No crabs to align".into()))
 }
 
-pub fn align_crabs_cost_linear() -> u32 {
-    let crabs = parse_input(INPUT);
-    best_align_cost(crabs, linear_cost).unwrap()
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Parsed = Vec<i32>;
+
+    fn parse(input: &str, _params: &Values, context: &Context) -> Result<Self::Parsed, ParseError> {
+        parse_input(input, context)
+    }
+
+    fn part1(crabs: &Self::Parsed, _params: &Values) -> Result<Answer, SolveError> {
+        align(crabs, constant_cost)
+    }
+
+    fn part2(crabs: &Self::Parsed, _params: &Values) -> Result<Answer, SolveError> {
+        align(crabs, linear_cost)
+    }
 }
 
 #[cfg(test)]
@@ -51,31 +72,46 @@ mod test {
     #[test]
     fn align_no_coord() {
         let crabs = vec![];
-        assert_eq!(None, best_align_cost(crabs, constant_cost));
+        assert_eq!(None, best_align_cost(&crabs, constant_cost));
     }
 
     #[test]
     fn align_single_coord() {
         let crabs = vec![4];
-        assert_eq!(Some(0), best_align_cost(crabs, constant_cost));
+        assert_eq!(Some(0), best_align_cost(&crabs, constant_cost));
     }
 
     #[test]
     fn align_two_coords() {
         let crabs = vec![4, 8];
-        assert_eq!(Some(4), best_align_cost(crabs, constant_cost));
+        assert_eq!(Some(4), best_align_cost(&crabs, constant_cost));
     }
 
     #[test]
     fn align_three_coords() {
         let crabs = vec![4, 8, 16];
-        assert_eq!(Some(12), best_align_cost(crabs, constant_cost));
+        assert_eq!(Some(12), best_align_cost(&crabs, constant_cost));
+    }
+
+    #[test]
+    fn parse_crab_input() {
+        let strict = Context::default();
+        assert_eq!(vec![16, 1, 2], parse_input("16,1,2\n", &strict).unwrap());
+        let err = parse_input("16,1,,2", &strict).unwrap_err();
+        assert_eq!((Some(1), 5), (err.line, err.column));
+        assert!(parse_input("\n", &strict).is_err());
+
+        // Leniently only the malformed positions are left out, but not all of them
+        let lenient = Context::new(parse::Mode::Lenient);
+        assert_eq!(vec![16, 2], parse_input("16,x,2", &lenient).unwrap());
+        assert!(parse_input("x,y", &lenient).is_err());
+        assert_eq!(3, lenient.into_skipped().len());
     }
 
     #[test]
     fn example_crabs() {
         let crabs = vec![16,1,2,0,4,2,7,1,2,14];
-        assert_eq!(Some(37), best_align_cost(crabs, constant_cost));
+        assert_eq!(Some(37), best_align_cost(&crabs, constant_cost));
     }
 
     #[test]
@@ -88,6 +124,6 @@ mod test {
     #[test]
     fn example_crabs_linear() {
         let crabs = vec![16,1,2,0,4,2,7,1,2,14];
-        assert_eq!(Some(168), best_align_cost(crabs, linear_cost));
+        assert_eq!(Some(168), best_align_cost(&crabs, linear_cost));
     }
 }
@@ -1,7 +1,8 @@
 use std::str::FromStr;
 use std::collections::BTreeSet;
 
-const INPUT: &str = include_str!("../data/8/input");
+use crate::params::Values;
+use crate::solution::{Answer, Context, ParseError, Solution, SolveError};
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
 enum Segment { A,B,C,D,E,F,G }
@@ -12,7 +13,7 @@ struct Segments (
 );
 
 #[derive(Debug, PartialEq, Eq)]
-struct Notes {
+pub struct Notes {
     patterns: Vec<Segments>,
     outputs: Vec<Segments>,
 }
@@ -23,17 +24,17 @@ struct DigitKey(
 );
 
 impl Segments {
-    fn char_to_segment(c: char) -> Segment {
+    fn char_to_segment(c: char) -> Option<Segment> {
         use Segment::*;
         match c {
-            'a' => {A},
-            'b' => {B},
-            'c' => {C},
-            'd' => {D},
-            'e' => {E},
-            'f' => {F},
-            'g' => {G},
-            _ => panic!("Unknown segment {}", c),
+            'a' => Some(A),
+            'b' => Some(B),
+            'c' => Some(C),
+            'd' => Some(D),
+            'e' => Some(E),
+            'f' => Some(F),
+            'g' => Some(G),
+            _ => None,
         }
     }
 
@@ -107,12 +108,12 @@ impl FromIterator<Segment> for Segments {
     }
 }
 
-#[derive(Debug)]
-struct ParseSegmentsErr{}
 impl FromStr for Segments {
-    type Err = ParseSegmentsErr;
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Segments, Self::Err> {
-        Ok(s.chars().map(Segments::char_to_segment).collect())
+        s.char_indices()
+            .map(|(i, c)| Segments::char_to_segment(c).ok_or_else(|| ParseError::at(s, &s[i..], "a segment a-g")))
+            .collect()
     }
 }
 
@@ -139,7 +140,7 @@ impl DigitKey {
     }
 }
 
-fn decode_patterns(patterns: &Vec<Segments>) -> DigitKey {
+fn decode_patterns(patterns: &[Segments]) -> DigitKey {
     let mut key = DigitKey::new();
     key.update(1, patterns.iter().find(|p| p.is_one()).expect("One pattern not found").clone());
     key.update(4, patterns.iter().find(|p| p.is_four()).expect("Four pattern not found").clone());
@@ -154,8 +155,8 @@ fn decode_patterns(patterns: &Vec<Segments>) -> DigitKey {
     key
 }
 
-fn decode_digits(digits: &Vec<Segments>, key: &DigitKey) -> Vec<u8> {
-    digits.iter().map(|d| key.decode(d).expect(&format!("No such digit: {:?}", d))).collect()
+fn decode_digits(digits: &[Segments], key: &DigitKey) -> Vec<u8> {
+    digits.iter().map(|d| key.decode(d).unwrap_or_else(|| panic!("No such digit: {:?}", d))).collect()
 }
 
 fn sum_digits(digits: Vec<u8>) -> u32 {
@@ -167,39 +168,36 @@ fn sum_digits(digits: Vec<u8>) -> u32 {
         .sum::<u32>()
 }
 
-fn parse_words(s: &str) -> Vec<Segments> {
+fn parse_words(s: &str) -> Result<Vec<Segments>, ParseError> {
     s
-        .split(char::is_whitespace)
-        .map(str::trim)
-        .map(|s| s.parse::<Segments>().expect("Unparsable word"))
+        .split_whitespace()
+        .map(|w| w.parse::<Segments>().map_err(|e| e.within(s, w)))
         .collect()
 }
 
-fn parse_line(s: &str) -> Notes {
-    let mut sections = s.split('|').map(str::trim);
-    Notes {
-        patterns: parse_words(sections.next().expect("Empty data line")),
-        outputs: parse_words(sections.next().expect("No section separator")),
-    }
+fn parse_line(s: &str) -> Result<Notes, ParseError> {
+    let (patterns, outputs) = s
+        .split_once('|')
+        .ok_or_else(|| ParseError::at(s, &s[s.len()..], "patterns | outputs"))?;
+    Ok(Notes {
+        patterns: parse_words(patterns).map_err(|e| e.within(s, patterns))?,
+        outputs: parse_words(outputs).map_err(|e| e.within(s, outputs))?,
+    })
 }
 
-fn parse_input(s: &str) -> Vec<Notes> {
-    s.trim()
-        .split('\n')
-        .map(str::trim)
-        .map(parse_line)
-        .collect()
+fn parse_input(s: &str, context: &Context) -> Result<Vec<Notes>, ParseError> {
+    context.lines(s, parse_line)
 }
 
-fn count_uniqe_segment_output_digits(notes: Vec<Notes>) -> usize {
+fn count_uniqe_segment_output_digits(notes: &[Notes]) -> usize {
     notes
-        .into_iter()
-        .flat_map(|n| n.outputs)
+        .iter()
+        .flat_map(|n| n.outputs.iter())
         .filter(|seg| seg.is_unique_segments_digit())
         .count()
 }
 
-fn sum_decoded_outputs(notes: &Vec<Notes>) -> usize {
+fn sum_decoded_outputs(notes: &[Notes]) -> usize {
     let mut total_sum: usize = 0;
     for n in notes.iter() {
         let key  = decode_patterns(&n.patterns);
@@ -210,14 +208,22 @@ fn sum_decoded_outputs(notes: &Vec<Notes>) -> usize {
     total_sum
 }
 
-pub fn count_digits_1478() -> usize {
-    let notes = parse_input(INPUT);
-    count_uniqe_segment_output_digits(notes)
-}
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Parsed = Vec<Notes>;
 
-pub fn sum_all_decoded_outputs() -> usize {
-    let notes = parse_input(INPUT);
-    sum_decoded_outputs(&notes)
+    fn parse(input: &str, _params: &Values, context: &Context) -> Result<Self::Parsed, ParseError> {
+        parse_input(input, context)
+    }
+
+    fn part1(notes: &Self::Parsed, _params: &Values) -> Result<Answer, SolveError> {
+        Ok(count_uniqe_segment_output_digits(notes).into())
+    }
+
+    fn part2(notes: &Self::Parsed, _params: &Values) -> Result<Answer, SolveError> {
+        Ok(sum_decoded_outputs(notes).into())
+    }
 }
 
 #[cfg(test)]
@@ -236,10 +242,20 @@ mod test {
         assert!(seg.0.contains(&Segment::G));
     }
 
+    #[test]
+    fn parse_segment_error() {
+        let err = "fbhad".parse::<Segments>().unwrap_err();
+        assert_eq!(2, err.column);
+        let err = parse_line("abcd ef ab cde | fg dxfg").unwrap_err();
+        assert_eq!(21, err.column);
+        let err = parse_line("abcd ef ab cde fg defg").unwrap_err();
+        assert_eq!(22, err.column);
+    }
+
     #[test]
     fn parse_notes_or_outputs() {
         let data = "abcd ef ab cde fg defg";
-        let words = parse_words(data);
+        let words = parse_words(data).unwrap();
         assert_eq!(6, words.len());
         assert_eq!("abcd".parse::<Segments>().unwrap(), words[0]);
         assert_eq!("defg".parse::<Segments>().unwrap(), words[5]);
@@ -248,17 +264,17 @@ mod test {
     #[test]
     fn parse_data_line() {
         let data = "abcd ef ab cde | fg defg";
-        let notes = parse_line(data);
+        let notes = parse_line(data).unwrap();
         assert_eq!(4, notes.patterns.len());
         assert_eq!(2, notes.outputs.len());
     }
 
-    const EXAMPLE: &str = include_str!("../data/8/example");
+    const EXAMPLE: &str = include_str!("../data/8/examples/example.in");
 
     #[test]
     fn example_count_1478() {
-        let notes = parse_input(EXAMPLE);
-        let count = count_uniqe_segment_output_digits(notes);
+        let notes = parse_input(EXAMPLE, &Context::default()).unwrap();
+        let count = count_uniqe_segment_output_digits(&notes);
         assert_eq!(26, count);
     }
 
@@ -339,13 +355,13 @@ mod test {
             let seg = Segments::from(i);
             key.update(i, seg.clone());
             assert_eq!(Some(seg.clone()), key.encode(i));
-            assert_eq!(Some(i as u8), key.decode(&seg));
+            assert_eq!(Some(i), key.decode(&seg));
         }
     }
 
     #[test]
     fn decode_example_patterns() {
-        let patterns = parse_line(EXAMPLE_LINE).patterns;
+        let patterns = parse_line(EXAMPLE_LINE).unwrap().patterns;
         let key  = decode_patterns(&patterns);
         let mut expected_key = DigitKey::new();
         for i in 0..=9 {
@@ -357,7 +373,7 @@ mod test {
 
     #[test]
     fn decode_example_line() {
-        let notes = parse_line(EXAMPLE_LINE);
+        let notes = parse_line(EXAMPLE_LINE).unwrap();
         let key  = decode_patterns(&notes.patterns);
         let digits = decode_digits(&notes.outputs, &key);
         let digit_sum = sum_digits(digits);
@@ -366,14 +382,16 @@ mod test {
 
     #[test]
     fn decode_full_example() {
-        let notes = parse_input(EXAMPLE);
+        let notes = parse_input(EXAMPLE, &Context::default()).unwrap();
         let total_sum = sum_decoded_outputs(&notes);
         assert_eq!(61229, total_sum);
     }
 
+    const INPUT: &str = include_str!("../data/8/input");
+
     #[test]
     fn decode_full_puzzle() {
-        let notes = parse_input(INPUT);
+        let notes = parse_input(INPUT, &Context::default()).unwrap();
         let total_sum = sum_decoded_outputs(&notes);
         assert_eq!(936117, total_sum);
     }
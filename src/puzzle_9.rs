@@ -1,91 +1,58 @@
 use std::str::FromStr;
 use std::collections::BTreeSet;
 
-const INPUT: &str = include_str!("../data/9/input");
+use crate::grid::{Connectivity, Grid, Pos};
+use crate::params::{Param, Values};
+use crate::solution::{Answer, Context, ParseError, Solution, SolveError};
 
-type Position = (isize, isize);
+pub type Position = Pos; // x is the column, y the row
+
+pub const BASINS: Param = Param{
+    name: "basins", description: "largest basins multiplied in part 2", default: 3, range: 1..=1000,
+};
+
+pub const PARAMS: &[Param] = &[BASINS];
 
 #[derive(Debug)]
-struct Heightmap{
-    data: Vec<u8>,
-    shape: (usize, usize) 
+pub struct Heightmap{
+    grid: Grid<u8>,
 }
 
-#[derive(Debug, PartialEq)]
-struct ParseHeightmapError(&'static str);
 impl FromStr for Heightmap {
-    type Err = ParseHeightmapError;
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut widths = s
-            .trim()
-            .split('\n')
-            .map(str::trim)
-            .map(str::chars)
-            .map(|l| l.count());
-
-        let width = widths.next().unwrap(); // One element is guaranteed (might be "")
-        if width == 0 {
-            return Err(ParseHeightmapError("Empty data"));
-        }
-        if !widths.all(|w| w == width) {
-            return Err(ParseHeightmapError("Ragged data shape"));
-        }
-
-        let data: Vec<u8> = s
-            .chars()
-            .filter(|c| c.is_digit(10))
-            .map(|c| u8::from_str(&c.to_string()).unwrap())
-            .collect();
-
-        let shape = (data.len()/width, width);
-        Ok(Heightmap{data, shape})
+        Ok(Heightmap{ grid: s.parse()? })
     }
 }
 
 impl Heightmap {
-    fn contains(self: &Self, pos: Position) -> bool {
-        (0..self.shape.0 as isize).contains(&pos.0) &&
-        (0..self.shape.1 as isize).contains(&pos.1)
-    }
-
-    fn get(self: &Self, pos: Position) -> Option<u8> {
-        if !self.contains(pos) {return None; }
-        self.data.get(pos.0 as usize * self.shape.1 + pos.1 as usize).map(|v| *v)
+    pub fn contains(self: &Self, pos: Position) -> bool {
+        self.grid.contains(pos)
     }
 
-    fn get_adjacent_pos(self: &Self, pos: Position) -> Vec<Position> {
-        let adj = vec![
-            (pos.0-1, pos.1),
-            (pos.0, pos.1-1),
-            (pos.0, pos.1+1),
-            (pos.0+1, pos.1),
-        ];
-        adj.into_iter().filter(|&a| self.contains(a)).collect()
+    pub fn get(self: &Self, pos: Position) -> Option<u8> {
+        self.grid.get(pos).copied()
     }
 
-    fn pos_to_idx(self: &Self, idx: usize) -> Position {
-        ((idx / self.shape.1) as isize, (idx % self.shape.1) as isize)
+    pub fn get_adjacent_pos(self: &Self, pos: Position) -> Vec<Position> {
+        self.grid.neighbours(pos, Connectivity::FOUR).collect()
     }
 
-    fn get_depth_pos(self: &Self) -> Vec<Position> {
-        let mut depths = vec![];
-        for (i, val) in self.data.iter().enumerate() {
-            let pos = self.pos_to_idx(i);
-            let adj = self.get_adjacent_pos(pos);
-            if adj.iter().all(|a| self.get(*a).unwrap()>*val) {
-                depths.push(pos);
-            }
-        }
-        depths
+    pub fn get_depth_pos(self: &Self) -> Vec<Position> {
+        self.grid
+            .iter()
+            .filter(|&(pos, val)| self.grid.neighbours(pos, Connectivity::FOUR).all(|a| self.grid[a] > *val))
+            .map(|(pos, _)| pos)
+            .collect()
     }
 
-    fn get_risk_levels(self: &Self) -> Vec<u8> {
+    pub fn get_risk_levels(self: &Self) -> Vec<u8> {
         self.get_depth_pos().into_iter().map(
             |pos| self.get(pos).expect("Depth pos not in this map")+1
         ).collect()
     }
 
-    fn fill_basin(self: &Self, pos: Position) -> usize {
+    pub fn fill_basin(self: &Self, pos: Position) -> usize {
         let mut basin = BTreeSet::<Position>::new();
         let mut todo = BTreeSet::<Position>::new();
         todo.insert(pos);
@@ -105,94 +72,106 @@ impl Heightmap {
         basin.len()
     }
 
-    fn get_basin_sizes(self: &Self) -> Vec::<usize> { 
+    pub fn get_basin_sizes(self: &Self) -> Vec::<usize> { 
         self.get_depth_pos()
             .into_iter()
             .map(|d| self.fill_basin(d))
             .collect()
     }
 
-    fn get_basin_score(self: &Self) -> usize {
+    /// The product of the sizes of the `count` largest basins, `None` if
+    /// there are fewer basins than that
+    pub fn get_basin_score(self: &Self, count: usize) -> Option<usize> {
         let mut basin_sizes = self.get_basin_sizes();
+        if basin_sizes.len() < count {
+            return None;
+        }
         basin_sizes.sort();
-        basin_sizes.into_iter().rev().take(3).reduce(|total, s| total*s).expect("Not enough basins found")
+        Some(basin_sizes.into_iter().rev().take(count).product())
     }
 }
 
-pub fn sum_risk_levels() -> usize {
-    let heightmap: Heightmap = INPUT.parse().unwrap();
-    heightmap.get_risk_levels().into_iter().map(|v| v as usize).sum()
-}
+pub struct Puzzle;
+
+impl Solution for Puzzle {
+    type Parsed = Heightmap;
 
-pub fn basin_risk_level() -> usize {
-    let heightmap: Heightmap = INPUT.parse().unwrap();
-    heightmap.get_basin_score()
+    fn parse(input: &str, _params: &Values, context: &Context) -> Result<Self::Parsed, ParseError> {
+        Ok(Heightmap{ grid: Grid::digits(input, context)? })
+    }
+
+    fn part1(heightmap: &Self::Parsed, _params: &Values) -> Result<Answer, SolveError> {
+        Ok(heightmap.get_risk_levels().into_iter().map(|v| v as usize).sum::<usize>().into())
+    }
+
+    fn part2(heightmap: &Self::Parsed, params: &Values) -> Result<Answer, SolveError> {
+        let count = BASINS.get(params);
+        heightmap
+            .get_basin_score(count)
+            .map(Answer::from)
+            .ok_or_else(|| SolveError(format!("Fewer than {} basins found", count)))
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    const EXAMPLE_HEIGHTMAP: &str = "
-        2199943210
-        3987894921
-        9856789892
-        8767896789
-        9899965678
-    ";
+    fn p(x: isize, y: isize) -> Position {
+        Position::new(x, y)
+    }
+
+    const EXAMPLE_HEIGHTMAP: &str = include_str!("../data/9/examples/example.in");
 
     #[test]
     fn parse_example_heightmap() {
         let heightmap: Heightmap = EXAMPLE_HEIGHTMAP.parse().unwrap();
-        assert_eq!(50, heightmap.data.len());
-        assert_eq!((5, 10), heightmap.shape);
+        assert_eq!(50, heightmap.grid.len());
+        assert_eq!((5, 10), heightmap.grid.shape());
     }
 
     #[test]
     fn parse_error_heightmap() {
-        match "".parse::<Heightmap>() {
-            Err(ParseHeightmapError(msg)) => assert_eq!(msg, "Empty data"),
-            Ok(_) => panic!("Empty string should not parse")
-        }
-        match "1\n12".parse::<Heightmap>() {
-            Err(ParseHeightmapError(msg)) => assert_eq!(msg, "Ragged data shape"),
-            Ok(_) => panic!("Ragged data should not parse")
-        }
+        let err = "".parse::<Heightmap>().unwrap_err();
+        assert_eq!("at least one row of digits", err.expected);
+        let err = "1\n12".parse::<Heightmap>().unwrap_err();
+        assert_eq!("1 digits per row", err.expected);
+        assert_eq!((Some(2), 1), (err.line, err.column));
     }
 
     #[test]
     fn index_heightmap() {
         let heightmap: Heightmap = EXAMPLE_HEIGHTMAP.parse().unwrap();
 
-        assert_eq!(Some(2), heightmap.get((0,0)));
-        assert_eq!(Some(9), heightmap.get((4,0)));
-        assert_eq!(Some(0), heightmap.get((0,9)));
-        assert_eq!(Some(8), heightmap.get((4,9)));
-        assert_eq!(Some(7), heightmap.get((2,4)));
+        assert_eq!(Some(2), heightmap.get(p(0, 0)));
+        assert_eq!(Some(9), heightmap.get(p(0, 4)));
+        assert_eq!(Some(0), heightmap.get(p(9, 0)));
+        assert_eq!(Some(8), heightmap.get(p(9, 4)));
+        assert_eq!(Some(7), heightmap.get(p(4, 2)));
 
-        assert_eq!(None, heightmap.get((-1,0)));
-        assert_eq!(None, heightmap.get((-1,9)));
-        assert_eq!(None, heightmap.get((5,0)));
-        assert_eq!(None, heightmap.get((5,9)));
+        assert_eq!(None, heightmap.get(p(0, -1)));
+        assert_eq!(None, heightmap.get(p(9, -1)));
+        assert_eq!(None, heightmap.get(p(0, 5)));
+        assert_eq!(None, heightmap.get(p(9, 5)));
 
-        assert_eq!(None, heightmap.get((0,-1)));
-        assert_eq!(None, heightmap.get((0,10)));
-        assert_eq!(None, heightmap.get((4,-1)));
-        assert_eq!(None, heightmap.get((4,10)));
+        assert_eq!(None, heightmap.get(p(-1, 0)));
+        assert_eq!(None, heightmap.get(p(10, 0)));
+        assert_eq!(None, heightmap.get(p(-1, 4)));
+        assert_eq!(None, heightmap.get(p(10, 4)));
     }
 
     #[test]
     fn get_adjacent_fields() {
         let heightmap: Heightmap = EXAMPLE_HEIGHTMAP.parse().unwrap();
-        let corner = heightmap.get_adjacent_pos((0,0));
-        let edge = heightmap.get_adjacent_pos((1,0));
-        let field = heightmap.get_adjacent_pos((1,1));
+        let corner = heightmap.get_adjacent_pos(p(0, 0));
+        let edge = heightmap.get_adjacent_pos(p(0, 1));
+        let field = heightmap.get_adjacent_pos(p(1, 1));
         assert_eq!(2, corner.len());
         assert_eq!(3, edge.len());
         assert_eq!(4, field.len());
-        assert_eq!(vec![(0,1), (1,0)], corner);
-        assert_eq!(vec![(0,0), (1,1), (2,0)], edge);
-        assert_eq!(vec![(0,1), (1,0), (1,2), (2, 1)], field);
+        assert_eq!(vec![p(1, 0), p(0, 1)], corner);
+        assert_eq!(vec![p(0, 0), p(1, 1), p(0, 2)], edge);
+        assert_eq!(vec![p(1, 0), p(0, 1), p(2, 1), p(1, 2)], field);
     }
 
     #[test]
@@ -210,7 +189,7 @@ mod test {
             555
         ".parse().unwrap();
 
-        let basin_size = heightmap.fill_basin((1,1));
+        let basin_size = heightmap.fill_basin(p(1, 1));
         assert_eq!(9, basin_size);
     }
 
@@ -223,7 +202,7 @@ mod test {
             9990
         ".parse().unwrap();
 
-        let basin_size = heightmap.fill_basin((1,1));
+        let basin_size = heightmap.fill_basin(p(1, 1));
         assert_eq!(7, basin_size);
     }
 
@@ -231,13 +210,30 @@ mod test {
     fn example_basin_score() {
         let heightmap: Heightmap = EXAMPLE_HEIGHTMAP.parse().unwrap();
         assert_eq!(vec![3, 9, 14, 9], heightmap.get_basin_sizes());
-        assert_eq!(1134, heightmap.get_basin_score());
+        assert_eq!(Some(1134), heightmap.get_basin_score(3));
+        assert_eq!(None, heightmap.get_basin_score(5));
     }
 
+    #[test]
+    fn not_enough_basins() {
+        let flat: Heightmap = "99\n99".parse().unwrap();
+        assert_eq!(None, flat.get_basin_score(1));
+        let err = Puzzle::part2(&flat, &Values::new()).unwrap_err();
+        assert_eq!("Fewer than 3 basins found", err.to_string());
+
+        let heightmap: Heightmap = EXAMPLE_HEIGHTMAP.parse().unwrap();
+        let values = Values::from([("basins", 4)]);
+        assert_eq!(Ok(Answer::from(3 * 9 * 14 * 9)), Puzzle::part2(&heightmap, &values));
+        let values = Values::from([("basins", 5)]);
+        assert!(Puzzle::part2(&heightmap, &values).is_err());
+    }
+
+    const INPUT: &str = include_str!("../data/9/input");
+
     #[test]
     fn solution_basin_score() {
         let heightmap: Heightmap = INPUT.parse().unwrap();
-        assert_eq!(920448, heightmap.get_basin_score());
+        assert_eq!(Some(920448), heightmap.get_basin_score(3));
     }
 
 }
@@ -0,0 +1,282 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::params::{Param, Values};
+use crate::solution::{parse_parts, Answer, Context, ParseError, Parts};
+use crate::puzzle_1;
+use crate::puzzle_2;
+use crate::puzzle_3;
+use crate::puzzle_4;
+use crate::puzzle_5;
+use crate::puzzle_6;
+use crate::puzzle_7;
+use crate::puzzle_8;
+use crate::puzzle_9;
+use crate::puzzle_10;
+use crate::puzzle_11;
+use crate::puzzle_12;
+use crate::puzzle_13;
+use crate::puzzle_14;
+
+/// Parses the input of a day with the resolved parameters
+pub type ParseFn = fn(&str, &Values, &Context) -> Result<Box<dyn Parts>, ParseError>;
+
+/// A day of the calendar. Each part description is printed with every
+/// `{param}` replaced by the value of that parameter and the first `{}` by
+/// the answer.
+pub struct Day {
+    pub day: u8,
+    pub descriptions: [&'static str; 2],
+    pub parse: ParseFn,
+    pub params: &'static [Param],
+}
+
+impl Day {
+    pub fn format(self: &Self, part: u8, answer: &Answer, params: &Values) -> String {
+        let description = self.params.iter().fold(
+            self.descriptions[part as usize - 1].to_string(),
+            |d, param| d.replace(&format!("{{{}}}", param.name), &param.get(params).to_string())
+        );
+        format!("Puzzle {}.{}: {}", self.day, part, description.replacen("{}", &answer.to_string(), 1))
+    }
+}
+
+pub const DAYS: &[Day] = &[
+    Day { day: 1, parse: parse_parts::<puzzle_1::Puzzle>, params: &[], descriptions: [
+        "There are {} instances of increased depth.",
+        "There are {} instances of increased depth using a sliding window of size 3.",
+    ]},
+    Day { day: 2, parse: parse_parts::<puzzle_2::Puzzle>, params: &[], descriptions: [
+        "The final posision metric of the sub (horizontal x depth) is {}.",
+        "The final posision metric of the sub (horizontal x depth) using the aimed algorthim is {}.",
+    ]},
+    Day { day: 3, parse: parse_parts::<puzzle_3::Puzzle>, params: puzzle_3::PARAMS, descriptions: [
+        "The power consumption (gamma x epsilon) is {}",
+        "The life rating (oxygen x co2 rating) is {}",
+    ]},
+    Day { day: 4, parse: parse_parts::<puzzle_4::Puzzle>, params: puzzle_4::PARAMS, descriptions: [
+        "The winning bingo board has the score {}",
+        "The loosing bingo board has the score {}",
+    ]},
+    Day { day: 5, parse: parse_parts::<puzzle_5::Puzzle>, params: &[], descriptions: [
+        "The number of points at which straight lines overlap is {}",
+        "The number of points at which straight and diagonal lines overlap is {}",
+    ]},
+    Day { day: 6, parse: parse_parts::<puzzle_6::Puzzle>, params: puzzle_6::PARAMS, descriptions: [
+        "After {part1_days} days the number of lanternfish is {}",
+        "After {part2_days} days the number of lanternfish is {}",
+    ]},
+    Day { day: 7, parse: parse_parts::<puzzle_7::Puzzle>, params: &[], descriptions: [
+        "The fuel cost to align the crabs is {}",
+        "The fuel cost to align the crabs with linearly increasing fuel cost is {}",
+    ]},
+    Day { day: 8, parse: parse_parts::<puzzle_8::Puzzle>, params: &[], descriptions: [
+        "The number of digitis that use unique segments (1,4,7,8) is {}",
+        "The sum of all decoded seven segment digits is {}",
+    ]},
+    Day { day: 9, parse: parse_parts::<puzzle_9::Puzzle>, params: puzzle_9::PARAMS, descriptions: [
+        "The sum of all risk levels is {}",
+        "The basin risk level is {}",
+    ]},
+    Day { day: 10, parse: parse_parts::<puzzle_10::Puzzle>, params: &[], descriptions: [
+        "The syntax error score is {}",
+        "The middle complettion score is {}",
+    ]},
+    Day { day: 11, parse: parse_parts::<puzzle_11::Puzzle>, params: puzzle_11::PARAMS, descriptions: [
+        "After {steps} steps, the number of octupus flashes is {}",
+        "All octupus flashes are in sync after {} steps.",
+    ]},
+    Day { day: 12, parse: parse_parts::<puzzle_12::Puzzle>, params: &[], descriptions: [
+        "The number of paths from start to end is {}",
+        "The number of paths from start to end with one double visit is {}",
+    ]},
+    Day { day: 13, parse: parse_parts::<puzzle_13::Puzzle>, params: &[], descriptions: [
+        "After one fold the number of visible dots is {}",
+        "The infrared system activation code is:\n{}",
+    ]},
+    Day { day: 14, parse: parse_parts::<puzzle_14::Puzzle>, params: puzzle_14::PARAMS, descriptions: [
+        "The difference between the number of the most and least common polymer element after {part1_steps} steps is {}",
+        "The difference between the number of the most and least common polymer element after {part2_steps} steps is {}",
+    ]},
+];
+
+/// The selected parts of a day, in ascending order
+pub struct Task {
+    pub day: &'static Day,
+    pub parts: Vec<u8>,
+}
+
+/// A part of the puzzle calendar requested on the command line
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Selector {
+    All,
+    Day(u8),
+    Part(u8, u8),
+    Range(u8, u8), // Inclusive
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseSelectorError(String);
+
+impl fmt::Display for ParseSelectorError {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid selection '{}'", self.0)
+    }
+}
+
+impl FromStr for Selector {
+    type Err = ParseSelectorError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseSelectorError(s.to_string());
+        let number = |n: &str| n.trim().parse::<u8>().map_err(|_| err());
+
+        if s == "all" {
+            Ok(Selector::All)
+        }
+        else if let Some((from, to)) = s.split_once("..") {
+            let to = to.strip_prefix('=').unwrap_or(to); // Accept 3..=7 as well
+            let (from, to) = (number(from)?, number(to)?);
+            if from > to { return Err(err()); }
+            Ok(Selector::Range(from, to))
+        }
+        else if let Some((day, part)) = s.split_once('.') {
+            Ok(Selector::Part(number(day)?, number(part)?))
+        }
+        else {
+            Ok(Selector::Day(number(s)?))
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SelectError {
+    UnknownDay(u8),
+    UnknownPart(u8, u8),
+}
+
+impl fmt::Display for SelectError {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SelectError::UnknownDay(day) => write!(f, "There is no solution for day {}", day),
+            SelectError::UnknownPart(day, part) => write!(f, "There is no solution for puzzle {}.{}", day, part),
+        }
+    }
+}
+
+const PARTS: [u8; 2] = [1, 2];
+
+fn find_day(day: u8) -> Result<&'static Day, SelectError> {
+    DAYS.iter().find(|d| d.day == day).ok_or(SelectError::UnknownDay(day))
+}
+
+/// Resolve the selectors to the selected parts of each day, ordered by day.
+/// Every explicitly requested day (including each day of a range) must exist.
+pub fn select(selectors: &[Selector]) -> Result<Vec<Task>, SelectError> {
+    let mut selected = BTreeMap::<u8, BTreeSet<u8>>::new();
+    for selector in selectors {
+        match *selector {
+            Selector::All => {
+                for day in DAYS {
+                    selected.entry(day.day).or_default().extend(PARTS);
+                }
+            }
+            Selector::Day(day) => {
+                find_day(day)?;
+                selected.entry(day).or_default().extend(PARTS);
+            }
+            Selector::Part(day, part) => {
+                find_day(day)?;
+                if !PARTS.contains(&part) { return Err(SelectError::UnknownPart(day, part)); }
+                selected.entry(day).or_default().insert(part);
+            }
+            Selector::Range(from, to) => {
+                for day in from..=to {
+                    find_day(day)?;
+                    selected.entry(day).or_default().extend(PARTS);
+                }
+            }
+        }
+    }
+    selected
+        .into_iter()
+        .map(|(day, parts)| Ok(Task{ day: find_day(day)?, parts: parts.into_iter().collect() }))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ids(tasks: Vec<Task>) -> Vec<(u8, u8)> {
+        tasks.into_iter().flat_map(|t| t.parts.into_iter().map(move |p| (t.day.day, p))).collect()
+    }
+
+    #[test]
+    fn parse_selector() {
+        assert_eq!(Selector::All, "all".parse().unwrap());
+        assert_eq!(Selector::Day(9), "9".parse().unwrap());
+        assert_eq!(Selector::Part(9, 2), "9.2".parse().unwrap());
+        assert_eq!(Selector::Range(3, 7), "3..7".parse().unwrap());
+        assert_eq!(Selector::Range(3, 7), "3..=7".parse().unwrap());
+    }
+
+    #[test]
+    fn parse_selector_error() {
+        assert!("".parse::<Selector>().is_err());
+        assert!("x".parse::<Selector>().is_err());
+        assert!("9.".parse::<Selector>().is_err());
+        assert!("..7".parse::<Selector>().is_err());
+        assert!("7..3".parse::<Selector>().is_err());
+        assert!("300".parse::<Selector>().is_err());
+    }
+
+    #[test]
+    fn format_with_params() {
+        let day = find_day(11).unwrap();
+        let answer = Answer::Integer(1656);
+        assert_eq!("Puzzle 11.1: After 100 steps, the number of octupus flashes is 1656", day.format(1, &answer, &Values::new()));
+        let text = day.format(1, &answer, &Values::from([("steps", 10)]));
+        assert_eq!("Puzzle 11.1: After 10 steps, the number of octupus flashes is 1656", text);
+    }
+
+    #[test]
+    fn registry_is_complete() {
+        for day in 1..=14 {
+            assert!(DAYS.iter().any(|d| d.day == day));
+        }
+    }
+
+    #[test]
+    fn select_day_and_part() {
+        assert_eq!(vec![(9, 1), (9, 2)], ids(select(&[Selector::Day(9)]).unwrap()));
+        assert_eq!(vec![(9, 2)], ids(select(&[Selector::Part(9, 2)]).unwrap()));
+    }
+
+    #[test]
+    fn select_is_ordered_and_unique() {
+        let tasks = select(&[Selector::Part(4, 2), Selector::Range(3, 4), Selector::Day(3)]).unwrap();
+        assert_eq!(vec![(3, 1), (3, 2), (4, 1), (4, 2)], ids(tasks));
+    }
+
+    #[test]
+    fn select_all() {
+        assert_eq!(DAYS.len(), select(&[Selector::All]).unwrap().len());
+    }
+
+    #[test]
+    fn select_unknown() {
+        assert!(matches!(select(&[Selector::Day(26)]), Err(SelectError::UnknownDay(26))));
+        assert!(matches!(select(&[Selector::Part(9, 3)]), Err(SelectError::UnknownPart(9, 3))));
+        assert!(matches!(select(&[Selector::Range(13, 16)]), Err(SelectError::UnknownDay(15))));
+    }
+
+    #[test]
+    fn format_answer() {
+        let day = find_day(6).unwrap();
+        assert_eq!(
+            "Puzzle 6.2: After 256 days the number of lanternfish is 42",
+            day.format(2, &Answer::Integer(42), &Values::new())
+        );
+    }
+}
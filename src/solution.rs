@@ -0,0 +1,157 @@
+use std::fmt;
+
+use crate::params::Values;
+pub use crate::parse::{Context, ParseError};
+
+/// The answer to a puzzle part: a number, or multi-line text such as the
+/// activation code drawn by day 13. `Unsigned` only holds the numbers beyond
+/// `i128::MAX`, smaller ones are always an `Integer`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Answer {
+    Integer(i128),
+    Unsigned(u128),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Integer(v) => write!(f, "{}", v),
+            Answer::Unsigned(v) => write!(f, "{}", v),
+            Answer::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+macro_rules! answer_from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(v: $t) -> Self {
+                    Answer::Integer(v as i128)
+                }
+            }
+        )*
+    }
+}
+
+answer_from_integer!(u8, u32, u64, usize, i32, i64, i128);
+
+/// Products of two 64 bit numbers may not fit an `i128`
+impl From<u128> for Answer {
+    fn from(v: u128) -> Self {
+        i128::try_from(v).map_or(Answer::Unsigned(v), Answer::Integer)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+/// Why a part has no answer although its input parsed, such as a result
+/// too large for the integers it is computed in
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SolveError(pub String);
+
+impl fmt::Display for SolveError {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A day of the calendar. The input is parsed once and both parts are
+/// solved from the parsed data. All steps are given the values of the day's
+/// parameters, see `params`, and the parse step the `Context` saying how to
+/// handle malformed lines.
+pub trait Solution {
+    type Parsed;
+    fn parse(input: &str, params: &Values, context: &Context) -> Result<Self::Parsed, ParseError>;
+    fn part1(parsed: &Self::Parsed, params: &Values) -> Result<Answer, SolveError>;
+    fn part2(parsed: &Self::Parsed, params: &Values) -> Result<Answer, SolveError>;
+}
+
+/// Object safe view on the parsed input of any day, so the runner can hold
+/// days with different `Parsed` types side by side
+pub trait Parts {
+    fn solve(self: &Self, part: u8) -> Result<Answer, SolveError>;
+}
+
+struct Parsed<S: Solution> {
+    parsed: S::Parsed,
+    params: Values,
+}
+
+impl<S: Solution> Parts for Parsed<S> {
+    fn solve(self: &Self, part: u8) -> Result<Answer, SolveError> {
+        match part {
+            1 => S::part1(&self.parsed, &self.params),
+            2 => S::part2(&self.parsed, &self.params),
+            _ => Err(SolveError(format!("There is no part {}", part))),
+        }
+    }
+}
+
+/// Parse `input`, keeping `params` to solve the parts with
+pub fn parse_parts<S>(input: &str, params: &Values, context: &Context) -> Result<Box<dyn Parts>, ParseError>
+    where S: Solution + 'static, S::Parsed: 'static
+{
+    Ok(Box::new(Parsed::<S>{ parsed: S::parse(input, params, context)?, params: params.clone() }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Sum;
+
+    impl Solution for Sum {
+        type Parsed = Vec<u32>;
+        fn parse(input: &str, _params: &Values, _context: &Context) -> Result<Self::Parsed, ParseError> {
+            input
+                .split(',')
+                .map(|n| n.parse::<u32>().map_err(|_| ParseError::at(input, n, "a number")))
+                .collect()
+        }
+        fn part1(parsed: &Self::Parsed, _params: &Values) -> Result<Answer, SolveError> {
+            parsed
+                .iter()
+                .try_fold(0u32, |sum, &v| sum.checked_add(v))
+                .map(Answer::from)
+                .ok_or_else(|| SolveError("sum overflows".into()))
+        }
+        fn part2(parsed: &Self::Parsed, _params: &Values) -> Result<Answer, SolveError> {
+            Ok(parsed.iter().map(u32::to_string).collect::<Vec<_>>().join("+").into())
+        }
+    }
+
+    #[test]
+    fn answer_display() {
+        assert_eq!("-12", Answer::from(-12i32).to_string());
+        assert_eq!("26984457539", Answer::from(26984457539usize).to_string());
+        assert_eq!("#.\n.#", Answer::from(String::from("#.\n.#")).to_string());
+        assert_eq!(Answer::Integer(7), Answer::from(7u128));
+        assert_eq!(Answer::Unsigned(u128::MAX), Answer::from(u128::MAX));
+        assert_eq!(u128::MAX.to_string(), Answer::from(u128::MAX).to_string());
+    }
+
+    #[test]
+    fn solve_parts() {
+        let parts = parse_parts::<Sum>("1,2,3", &Values::new(), &Context::default()).unwrap();
+        assert_eq!(Ok(Answer::Integer(6)), parts.solve(1));
+        assert_eq!(Ok(Answer::Text("1+2+3".into())), parts.solve(2));
+    }
+
+    #[test]
+    fn solve_error() {
+        let parts = parse_parts::<Sum>("4294967295,1", &Values::new(), &Context::default()).unwrap();
+        assert_eq!(Err(SolveError("sum overflows".into())), parts.solve(1));
+        assert_eq!(Err(SolveError("There is no part 3".into())), parts.solve(3));
+    }
+
+    #[test]
+    fn parse_error() {
+        assert!(parse_parts::<Sum>("1,x", &Values::new(), &Context::default()).is_err());
+    }
+}
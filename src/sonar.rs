@@ -0,0 +1,185 @@
+//! Summary statistics and anomalies of a day 1 sonar sweep.
+//!
+//! The depths are read line by line with `puzzle_1::parse_depth`, as
+//! `puzzle_1::parse_input` does, so a sweep accepts exactly the input the
+//! puzzle does. Each depth keeps the line it was read from, for reporting
+//! the jumps.
+
+use std::fmt;
+
+use crate::parse::Context;
+use crate::puzzle_1;
+use crate::solution::ParseError;
+
+/// Characters of the sparkline, from the shallowest to the deepest
+const RAMP: &[u8] = b"_.-~=+*#%@";
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Summary {
+    pub count: usize,
+    pub min: i32,
+    pub max: i32,
+    pub mean: f64,
+}
+
+impl fmt::Display for Summary {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} depths: min {}, max {}, mean {:.1}", self.count, self.min, self.max, self.mean)
+    }
+}
+
+/// A change between two consecutive depths
+#[derive(Debug, PartialEq, Clone)]
+pub struct Jump {
+    pub line: usize,    // Of the second depth
+    pub from: i32,
+    pub to: i32,
+}
+
+impl Jump {
+    pub fn delta(self: &Self) -> i64 {
+        self.to as i64 - self.from as i64
+    }
+}
+
+impl fmt::Display for Jump {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {} -> {} ({:+})", self.line, self.from, self.to, self.delta())
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Sweep {
+    depths: Vec<i32>,
+    lines: Vec<usize>,
+}
+
+impl Sweep {
+    pub fn parse(input: &str, context: &Context) -> Result<Self, ParseError> {
+        let numbered = context.numbered_lines(input, |line, text| Ok((line, puzzle_1::parse_depth(text)?)))?;
+        let (lines, depths) = numbered.into_iter().unzip();
+        Ok(Sweep{ depths, lines })
+    }
+
+    pub fn depths(self: &Self) -> &[i32] {
+        &self.depths
+    }
+
+    /// `None` for an empty sweep
+    pub fn summary(self: &Self) -> Option<Summary> {
+        let min = *self.depths.iter().min()?;
+        let max = *self.depths.iter().max()?;
+        let sum: i64 = self.depths.iter().map(|&d| d as i64).sum();
+        Some(Summary{ count: self.depths.len(), min, max, mean: sum as f64 / self.depths.len() as f64 })
+    }
+
+    /// The mean of each run of `window` consecutive depths, empty if there
+    /// are fewer depths than that. Panics if `window` is 0.
+    pub fn moving_average(self: &Self, window: usize) -> Vec<f64> {
+        assert!(window > 0, "The moving average window must not be empty");
+        if self.depths.len() < window { return Vec::new(); }
+        let mut sum: i64 = self.depths[..window].iter().map(|&d| d as i64).sum();
+        let mut averages = vec![sum as f64 / window as f64];
+        for (leaving, entering) in self.depths.iter().zip(&self.depths[window..]) {
+            sum += *entering as i64 - *leaving as i64;
+            averages.push(sum as f64 / window as f64);
+        }
+        averages
+    }
+
+    /// The changes between consecutive depths larger than `threshold` either way
+    pub fn jumps(self: &Self, threshold: u32) -> Vec<Jump> {
+        self.depths
+            .windows(2)
+            .zip(self.lines.iter().skip(1))
+            .map(|(pair, &line)| Jump{ line, from: pair[0], to: pair[1] })
+            .filter(|jump| jump.delta().unsigned_abs() > threshold as u64)
+            .collect()
+    }
+
+    /// The depth curve squeezed into at most `width` characters, each the
+    /// mean of its share of the depths, deeper ones drawn denser
+    pub fn sparkline(self: &Self, width: usize) -> String {
+        let depths: Vec<f64> = self.depths.iter().map(|&d| d as f64).collect();
+        sparkline(&depths, width)
+    }
+}
+
+/// `values` as a line of at most `width` characters, see `Sweep::sparkline`
+pub fn sparkline(values: &[f64], width: usize) -> String {
+    if values.is_empty() || width == 0 { return String::new(); }
+    let columns = width.min(values.len());
+    let buckets: Vec<f64> = (0..columns)
+        .map(|c| {
+            let bucket = &values[c * values.len() / columns..(c + 1) * values.len() / columns];
+            bucket.iter().sum::<f64>() / bucket.len() as f64
+        })
+        .collect();
+    let min = buckets.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = buckets.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    buckets
+        .iter()
+        .map(|&v| {
+            let level = if max > min { ((v - min) / (max - min) * (RAMP.len() - 1) as f64).round() as usize } else { 0 };
+            RAMP[level] as char
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse::Mode;
+
+    const EXAMPLE: &str = include_str!("../data/1/examples/example.in");
+
+    #[test]
+    fn summarise_example() {
+        let sweep = Sweep::parse(EXAMPLE, &Context::default()).unwrap();
+        let summary = sweep.summary().unwrap();
+        assert_eq!((10, 199, 269), (summary.count, summary.min, summary.max));
+        assert_eq!("10 depths: min 199, max 269, mean 225.6", summary.to_string());
+        assert_eq!(None, Sweep::parse("\n", &Context::default()).unwrap().summary());
+    }
+
+    #[test]
+    fn moving_average() {
+        let sweep = Sweep::parse("1\n2\n6\n\n3\n", &Context::default()).unwrap();
+        assert_eq!(vec![3.0, 11.0 / 3.0], sweep.moving_average(3));
+        assert_eq!(vec![1.0, 2.0, 6.0, 3.0], sweep.moving_average(1));
+        assert!(sweep.moving_average(5).is_empty());
+    }
+
+    #[test]
+    fn jumps_with_lines() {
+        let sweep = Sweep::parse("100\n105\n\n140\n120\n", &Context::default()).unwrap();
+        assert_eq!(
+            vec![Jump{ line: 4, from: 105, to: 140 }, Jump{ line: 5, from: 140, to: 120 }],
+            sweep.jumps(10)
+        );
+        assert_eq!("line 5: 140 -> 120 (-20)", sweep.jumps(10)[1].to_string());
+        assert!(sweep.jumps(35).is_empty());
+        assert!(Sweep::parse("", &Context::default()).unwrap().jumps(0).is_empty());
+    }
+
+    #[test]
+    fn lenient_lines() {
+        let lenient = Context::new(Mode::Lenient);
+        let sweep = Sweep::parse("100\nx\n\n140\n120\n", &lenient);
+        assert_eq!(Some(2), lenient.into_skipped()[0].line);
+        assert_eq!(
+            vec![Jump{ line: 4, from: 100, to: 140 }, Jump{ line: 5, from: 140, to: 120 }],
+            sweep.unwrap().jumps(10)
+        );
+    }
+
+    #[test]
+    fn draw_sparkline() {
+        let sweep = Sweep::parse("0\n9\n3\n6\n", &Context::default()).unwrap();
+        assert_eq!("_@~*", sweep.sparkline(10));
+        // Pairs averaged to 4.5 and 4.5
+        assert_eq!("__", sweep.sparkline(2));
+        assert_eq!("", Sweep::parse("", &Context::default()).unwrap().sparkline(10));
+        assert_eq!("_@", sparkline(&[1.0, 2.0], 80));
+    }
+}
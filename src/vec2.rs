@@ -0,0 +1,229 @@
+//! A 2D point or vector.
+//!
+//! `x` grows to the right and `y` downwards, as in the puzzle drawings: in
+//! a [`crate::grid::Grid`] `x` is the column and `y` the row. `UP` therefore
+//! points to smaller `y`.
+
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use crate::parse;
+use crate::solution::ParseError;
+
+/// The signed integers a `Vec2` can be made of
+pub trait Scalar:
+    Copy + Ord + fmt::Debug
+    + Add<Output=Self> + Sub<Output=Self> + Mul<Output=Self> + Neg<Output=Self>
+    + FromStr
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const MINUS_ONE: Self;
+    fn abs(self: Self) -> Self;
+    fn signum(self: Self) -> Self;
+}
+
+macro_rules! scalar {
+    ($($t:ty),*) => {
+        $(
+            impl Scalar for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const MINUS_ONE: Self = -1;
+                fn abs(self: Self) -> Self { <$t>::abs(self) }
+                fn signum(self: Self) -> Self { <$t>::signum(self) }
+            }
+        )*
+    }
+}
+
+scalar!(i32, i64, i128, isize);
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Default)]
+pub struct Vec2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Vec2<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Vec2{ x, y }
+    }
+
+    /// Apply `f` to both coordinates, e.g. to convert between scalar types
+    pub fn map<U, F: Fn(T) -> U>(self: Self, f: F) -> Vec2<U> {
+        Vec2{ x: f(self.x), y: f(self.y) }
+    }
+}
+
+impl<T: Scalar> Vec2<T> {
+    pub const ZERO: Self = Vec2::new(T::ZERO, T::ZERO);
+    pub const UP: Self = Vec2::new(T::ZERO, T::MINUS_ONE);
+    pub const DOWN: Self = Vec2::new(T::ZERO, T::ONE);
+    pub const LEFT: Self = Vec2::new(T::MINUS_ONE, T::ZERO);
+    pub const RIGHT: Self = Vec2::new(T::ONE, T::ZERO);
+
+    /// The four orthogonal directions, in reading order
+    pub const ORTHOGONAL: [Self; 4] = [Self::UP, Self::LEFT, Self::RIGHT, Self::DOWN];
+
+    /// All eight directions including diagonals, in reading order
+    pub const ALL_DIRECTIONS: [Self; 8] = [
+        Vec2::new(T::MINUS_ONE, T::MINUS_ONE), Self::UP, Vec2::new(T::ONE, T::MINUS_ONE),
+        Self::LEFT, Self::RIGHT,
+        Vec2::new(T::MINUS_ONE, T::ONE), Self::DOWN, Vec2::new(T::ONE, T::ONE),
+    ];
+
+    pub fn manhattan(self: Self, other: Self) -> T {
+        let d = other - self;
+        d.x.abs() + d.y.abs()
+    }
+
+    pub fn chebyshev(self: Self, other: Self) -> T {
+        let d = other - self;
+        d.x.abs().max(d.y.abs())
+    }
+
+    /// The direction of each coordinate: -1, 0 or 1
+    pub fn signum(self: Self) -> Self {
+        Vec2::new(self.x.signum(), self.y.signum())
+    }
+
+    /// Componentwise minimum
+    pub fn min(self: Self, other: Self) -> Self {
+        Vec2::new(self.x.min(other.x), self.y.min(other.y))
+    }
+
+    /// Componentwise maximum
+    pub fn max(self: Self, other: Self) -> Self {
+        Vec2::new(self.x.max(other.x), self.y.max(other.y))
+    }
+}
+
+/// The smallest and largest corner of the box enclosing all `points`
+/// (inclusive), `None` if there are no points
+pub fn bounding_box<T, I>(points: I) -> Option<(Vec2<T>, Vec2<T>)>
+    where T: Scalar, I: IntoIterator<Item=Vec2<T>>
+{
+    points
+        .into_iter()
+        .fold(None, |bounds, p| match bounds {
+            None => Some((p, p)),
+            Some((min, max)) => Some((p.min(min), p.max(max))),
+        })
+}
+
+impl<T: Scalar> Add for Vec2<T> {
+    type Output = Self;
+    fn add(self: Self, other: Self) -> Self {
+        Vec2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<T: Scalar> Sub for Vec2<T> {
+    type Output = Self;
+    fn sub(self: Self, other: Self) -> Self {
+        Vec2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<T: Scalar> AddAssign for Vec2<T> {
+    fn add_assign(self: &mut Self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl<T: Scalar> SubAssign for Vec2<T> {
+    fn sub_assign(self: &mut Self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl<T: Scalar> Mul<T> for Vec2<T> {
+    type Output = Self;
+    fn mul(self: Self, scalar: T) -> Self {
+        Vec2::new(self.x * scalar, self.y * scalar)
+    }
+}
+
+impl<T: Scalar> Neg for Vec2<T> {
+    type Output = Self;
+    fn neg(self: Self) -> Self {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Vec2<T> {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+/// Parses `x,y`, spaces around the coordinates are allowed
+impl<T: Scalar> FromStr for Vec2<T> {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut coords = s.split(',').map(str::trim);
+        match (coords.next(), coords.next(), coords.next()) {
+            (Some(x), Some(y), None) => {
+                Ok(Vec2::new(parse::token(s, x, "a coordinate")?, parse::token(s, y, "a coordinate")?))
+            }
+            (_, _, Some(z)) => Err(ParseError::at(s, z, "only two coordinates")),
+            _ => Err(ParseError::at(s, &s[s.len()..], "a point x,y")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    type V = Vec2<i32>;
+
+    #[test]
+    fn arithmetic() {
+        let (a, b) = (V::new(1, 2), V::new(4, -2));
+        assert_eq!(V::new(5, 0), a + b);
+        assert_eq!(V::new(3, -4), b - a);
+        assert_eq!(V::new(3, 6), a * 3);
+        assert_eq!(V::new(-1, -2), -a);
+        let mut c = a;
+        c += V::RIGHT;
+        c -= V::UP;
+        assert_eq!(V::new(2, 3), c);
+    }
+
+    #[test]
+    fn distances() {
+        let (a, b) = (V::new(1, 2), V::new(4, -2));
+        assert_eq!(7, a.manhattan(b));
+        assert_eq!(4, a.chebyshev(b));
+        assert_eq!(V::new(1, -1), (b - a).signum());
+        assert_eq!(V::ZERO, V::ZERO.signum());
+    }
+
+    #[test]
+    fn directions() {
+        assert_eq!(V::new(0, -1), V::UP);
+        assert_eq!(V::ZERO, V::ORTHOGONAL.into_iter().fold(V::ZERO, |a, b| a + b));
+        assert_eq!(V::ZERO, V::ALL_DIRECTIONS.into_iter().fold(V::ZERO, |a, b| a + b));
+        assert!(V::ALL_DIRECTIONS.iter().all(|&d| V::ZERO.chebyshev(d) == 1));
+    }
+
+    #[test]
+    fn bounds() {
+        let points = [V::new(3, -1), V::new(0, 4), V::new(2, 2)];
+        assert_eq!(Some((V::new(0, -1), V::new(3, 4))), bounding_box(points));
+        assert_eq!(None, bounding_box(Vec::<V>::new()));
+    }
+
+    #[test]
+    fn parse_vec2() {
+        assert_eq!(Ok(V::new(3, -4)), " 3, -4".parse());
+        assert_eq!("3,-4", V::new(3, -4).to_string());
+        assert_eq!(2, "1,x".parse::<V>().unwrap_err().column);
+        assert_eq!(4, "1,2,3".parse::<V>().unwrap_err().column);
+        assert!("".parse::<V>().is_err());
+        assert!("1,".parse::<V>().is_err());
+    }
+}
@@ -0,0 +1,128 @@
+use advent_of_code::puzzle_9::Heightmap;
+use advent_of_code::puzzle_11::OctoMap;
+use advent_of_code::puzzle_12::{CaveMap, Path};
+use advent_of_code::puzzle_14::{parse_rules, Polymer};
+use advent_of_code::params::Values;
+use advent_of_code::parse::Context;
+use advent_of_code::solution::{Answer, Solution};
+use advent_of_code::vec2::Vec2;
+use advent_of_code::{answers, examples, inputs, ocr, registry, puzzle_4, puzzle_13};
+
+const BINGO: &str = "\
+7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7
+";
+
+#[test]
+fn bingo_through_solution() {
+    let parsed = puzzle_4::Puzzle::parse(BINGO, &Values::new(), &Context::default()).unwrap();
+    assert_eq!(Ok(Answer::Integer(4512)), puzzle_4::Puzzle::part1(&parsed, &Values::new()));
+    assert_eq!(Ok(Answer::Integer(1924)), puzzle_4::Puzzle::part2(&parsed, &Values::new()));
+}
+
+#[test]
+fn bingo_board() {
+    let mut board = puzzle_4::Board::new(&[1, 2, 3, 4], (2, 2)).unwrap();
+    board.check(1);
+    assert_eq!(None, board.score(puzzle_4::STANDARD));
+    board.check(2);
+    assert_eq!(Some(14), board.score(puzzle_4::STANDARD));
+}
+
+#[test]
+fn heightmap() {
+    let heightmap: Heightmap = "2199943210\n3987894921\n9856789892\n8767896789\n9899965678".parse().unwrap();
+    assert_eq!(Some(2), heightmap.get(Vec2::new(0, 0)));
+    assert_eq!(vec![2, 1, 6, 6], heightmap.get_risk_levels());
+    assert_eq!(Some(1134), heightmap.get_basin_score(3));
+    assert_eq!(Some(14 * 9), heightmap.get_basin_score(2));
+}
+
+#[test]
+fn octo_map() {
+    let mut octo: OctoMap = include_str!("../data/11/examples/example.in").parse().unwrap();
+    assert_eq!(Some(5), octo.get(Vec2::new(0, 0)));
+    let flashes: u64 = (0..10).map(|_| octo.step()).sum();
+    assert_eq!(204, flashes);
+}
+
+#[test]
+fn cave_map() {
+    let map: CaveMap = include_str!("../data/12/examples/example1.in").parse().unwrap();
+    assert_eq!(10, map.count_paths(Path::single_visit_validator));
+    assert_eq!(36, map.count_paths(Path::double_visit_validator));
+}
+
+#[test]
+fn polymer() {
+    let mut polymer = Polymer::from("NNCB");
+    let (_, rules) = include_str!("../data/14/examples/example.in").split_once("\n\n").unwrap();
+    let rules = parse_rules(rules).unwrap();
+    polymer.polymerize(&rules, 10);
+    let counts = polymer.counts();
+    assert_eq!(1588, counts.max() - counts.min());
+}
+
+#[test]
+fn activation_code_is_text() {
+    let parsed = puzzle_13::Puzzle::parse(include_str!("../data/13/examples/example.in"), &Values::new(), &Context::default()).unwrap();
+    assert_eq!(Ok(Answer::Integer(17)), puzzle_13::Puzzle::part1(&parsed, &Values::new()));
+    match puzzle_13::Puzzle::part2(&parsed, &Values::new()).unwrap() {
+        Answer::Text(code) => assert_eq!("#####\n#   #\n#   #\n#   #\n#####\n", code),
+        other => panic!("Expected text, got {:?}", other),
+    }
+}
+
+#[test]
+fn recorded_answers() {
+    let data_dir = std::path::Path::new(inputs::DATA_DIR);
+    for day in registry::DAYS {
+        let recorded = answers::load(data_dir, day.day).unwrap();
+        let parsed = (day.parse)(&inputs::Source::Default.load(day.day).unwrap(), &Values::new(), &Context::default()).unwrap();
+        for part in [1, 2] {
+            assert_eq!(answers::Verdict::PASS, recorded.verify(part, &parsed.solve(part).unwrap()), "Day {}.{}", day.day, part);
+        }
+    }
+}
+
+#[test]
+fn examples_pass() {
+    let data_dir = std::path::Path::new(inputs::DATA_DIR);
+    for day in registry::DAYS {
+        let examples = examples::load(data_dir, day.day).unwrap();
+        assert!(!examples.is_empty(), "Day {} has no examples", day.day);
+        for example in examples {
+            let parsed = (day.parse)(&example.input, &Values::new(), &Context::default()).unwrap();
+            for part in [1, 2] {
+                let verdict = example.expected.verify(part, &parsed.solve(part).unwrap());
+                assert_eq!(answers::Verdict::PASS, verdict, "Day {}.{} {}", day.day, part, example.name);
+            }
+        }
+    }
+}
+
+#[test]
+fn activation_code_letters() {
+    let parsed = puzzle_13::Puzzle::parse(include_str!("../data/13/input"), &Values::new(), &Context::default()).unwrap();
+    match puzzle_13::Puzzle::part2(&parsed, &Values::new()).unwrap() {
+        Answer::Text(code) => assert_eq!(Some("HKUJGAJZ".to_string()), ocr::decode(&code)),
+        other => panic!("Expected text, got {:?}", other),
+    }
+}